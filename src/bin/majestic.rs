@@ -1,86 +1,442 @@
-static TIMESTAMP: &'static str = include_str!(concat!(env!("OUT_DIR"), "/timestamp.txt"));
-static VERSION: &'static str = include_str!(concat!(env!("OUT_DIR"), "/version.txt"));
-static TARGET: &'static str = include_str!(concat!(env!("OUT_DIR"), "/target.txt"));
+static TIMESTAMP: &str = include_str!(concat!(env!("OUT_DIR"), "/timestamp.txt"));
+static VERSION: &str = include_str!(concat!(env!("OUT_DIR"), "/version.txt"));
+static TARGET: &str = include_str!(concat!(env!("OUT_DIR"), "/target.txt"));
 
 use colored::*;
 use log::{debug, error, info};
 use majestic::{parser, printer, vm};
+use rustyline::completion::Completer;
 use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::{Hint, Hinter};
 use rustyline::validate::{ValidationContext, ValidationResult, Validator};
 use rustyline::Editor;
-use rustyline_derive::{Completer, Helper, Highlighter, Hinter};
+use rustyline_derive::Helper;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::io::IsTerminal;
+use std::rc::Rc;
 
 use parser::combinators::Combinators;
+use parser::tokens::{balance, matching_delimiter, tokenize, Balance, TokenKind};
 use vm::VirtualMachine;
 
+// Exit status of a script run
+const EXIT_RUNTIME_ERROR: i32 = 1;
+const EXIT_PARSE_ERROR: i32 = 2;
+const EXIT_IO_ERROR: i32 = 3;
+const EXIT_USAGE_ERROR: i32 = 64;
+
+/// Enables logging as configured by logcfg.yml on the working directory.
+/// Only the REPL does so, since batch output must hold nothing but results.
 fn load_log_config() {
     let mut cfg = std::env::current_dir().unwrap();
     cfg.push("logcfg.yml");
 
     if cfg.exists() {
-        println!("Loading log config...");
+        eprintln!("Loading log config...");
         match log4rs::init_file(cfg.clone(), Default::default()) {
             Ok(_) => info!("Loaded log config file: {}", cfg.display()),
-            Err(e) => println!("Could not load log config: {:?}", e),
+            Err(e) => eprintln!("Could not load log config: {:?}", e),
+        };
+    }
+}
+
+/// Settings of a REPL session, changed by commands.
+struct ReplState {
+    ast: bool,
+    echo: bool,
+    time: bool,
+    pretty: bool,
+    prelude: bool,
+    quit: bool,
+}
+
+/// REPL command, invoked as `#name ARGS`.
+/// - `args`: Description of the arguments, for help.
+/// - `run`: Runs the command with its unparsed arguments.
+struct ReplCommand {
+    name: &'static str,
+    args: &'static str,
+    help: &'static str,
+    run: fn(&mut Box<VirtualMachine>, &mut ReplState, &str),
+}
+
+const REPL_COMMANDS: &[ReplCommand] = &[
+    ReplCommand {
+        name: "help",
+        args: "[COMMAND]",
+        help: "Show help for all commands, or for a single one",
+        run: |_, _, args| print_help(args),
+    },
+    ReplCommand {
+        name: "load",
+        args: "FILE",
+        help: "Load a source file, as the load function does",
+        run: |vm, _, args| {
+            if args.is_empty() {
+                eprintln!("Missing file name. Usage: #load FILE");
+                return;
+            }
+            match vm.load_file(args) {
+                Ok(ret) => {
+                    printer::print_object(vm, &ret);
+                    println!();
+                }
+                Err(e) => report_runtime_error(vm, &e),
+            }
+        },
+    },
+    ReplCommand {
+        name: "reset",
+        args: "",
+        help: "Replace the virtual machine by a fresh one",
+        run: |vm, state, _| {
+            match new_vm(state.prelude) {
+                Ok(new) => *vm = new,
+                Err(e) => {
+                    eprintln!("Could not load the prelude: {}", e);
+                    return;
+                }
+            }
+            vm.set_argv(&[]).expect("Bind *argv*");
+            println!("{}", "Virtual machine reset".dimmed());
+        },
+    },
+    ReplCommand {
+        name: "time",
+        args: "",
+        help: "Toggle printing the time taken by each evaluation",
+        run: |_, state, _| state.time = !state.time,
+    },
+    ReplCommand {
+        name: "pretty",
+        args: "",
+        help: "Toggle printing results over multiple lines, within *print-right-margin*",
+        run: |_, state, _| state.pretty = !state.pretty,
+    },
+    ReplCommand {
+        name: "quit",
+        args: "",
+        help: "Leave the REPL",
+        run: |_, state, _| state.quit = true,
+    },
+    ReplCommand {
+        name: "ast",
+        args: "",
+        help: "Toggle printing the syntax tree and pointer of each expression",
+        run: |_, state, _| state.ast = !state.ast,
+    },
+    ReplCommand {
+        name: "echo",
+        args: "",
+        help: "Toggle printing each expression before evaluating it",
+        run: |_, state, _| state.echo = !state.echo,
+    },
+    ReplCommand {
+        name: "debrief",
+        args: "",
+        help: "Print usage statistics of the virtual machine",
+        run: |vm, _, _| vm.debrief(),
+    },
+    ReplCommand {
+        name: "atom",
+        args: "",
+        help: "Print the atom table",
+        run: |vm, _, _| vm.print_atom_table(),
+    },
+    ReplCommand {
+        name: "number",
+        args: "",
+        help: "Print the number table",
+        run: |vm, _, _| vm.print_number_table(),
+    },
+    ReplCommand {
+        name: "list",
+        args: "",
+        help: "Print the list area",
+        run: |vm, _, _| vm.print_list_area(),
+    },
+    ReplCommand {
+        name: "reg",
+        args: "",
+        help: "Print the registers",
+        run: |vm, _, _| vm.print_registers(),
+    },
+    ReplCommand {
+        name: "env",
+        args: "N",
+        help: "Print the environment of index N",
+        run: |vm, _, args| match args.parse() {
+            Ok(num) => vm.print_env(num),
+            Err(_) => println!("Could not parse environment number"),
+        },
+    },
+];
+
+fn find_command(name: &str) -> Option<&'static ReplCommand> {
+    REPL_COMMANDS.iter().find(|command| command.name == name)
+}
+
+/// Whether a line of input is a REPL command. Commands are named, so lines
+/// such as `#1=(a . #1#)` are read as expressions instead.
+fn command_p(line: &str) -> bool {
+    let mut chars = line.trim_start().chars();
+    (chars.next() == Some('#')) && chars.next().is_some_and(char::is_alphabetic)
+}
+
+fn print_help(args: &str) {
+    let usage = |command: &ReplCommand| {
+        format!("#{} {}", command.name, command.args)
+            .trim_end()
+            .to_owned()
+    };
+
+    if args.is_empty() {
+        println!("Commands:");
+        for command in REPL_COMMANDS {
+            println!("  {:<18}{}", usage(command), command.help);
+        }
+        return;
+    }
+
+    match find_command(args.trim_start_matches('#')) {
+        Some(command) => println!("{}\n  {}", usage(command), command.help),
+        None => eprintln!("Unknown command #{}.", args.trim_start_matches('#')),
+    }
+}
+
+// Special forms, highlighted on the REPL
+const SPECIAL_FORMS: &[&str] = &[
+    "fn",
+    "mac",
+    "setq",
+    "if",
+    "quote",
+    "quasiquote",
+    "do",
+    "defvar",
+    "defparameter",
+    "catch",
+    "catch-error",
+    "unwind-protect",
+];
+
+#[derive(Helper)]
+struct MajInputValidator {
+    vm: Rc<RefCell<Box<VirtualMachine>>>,
+}
+
+impl Completer for MajInputValidator {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        // Complete only the token under the cursor
+        let start = line[..pos]
+            .rfind(|c: char| c.is_whitespace() || "()[]'`,@\";".contains(c))
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let prefix = &line[start..pos];
+
+        let candidates = if prefix.starts_with('#') && line[..start].trim().is_empty() {
+            REPL_COMMANDS
+                .iter()
+                .map(|command| format!("#{}", command.name))
+                .filter(|command| command.starts_with(prefix))
+                .collect()
+        } else if prefix.is_empty() {
+            vec![]
+        } else {
+            self.vm.borrow().complete_symbol(prefix)
         };
+
+        Ok((start, candidates))
+    }
+}
+
+/// Hint showing the arguments of the function being called. It is only
+/// displayed, never inserted on the line.
+struct SignatureHint(String);
+
+impl Hint for SignatureHint {
+    fn display(&self) -> &str {
+        &self.0
+    }
+
+    fn completion(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl Hinter for MajInputValidator {
+    type Hint = SignatureHint;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &rustyline::Context<'_>) -> Option<SignatureHint> {
+        // Only hint right after `(name `, at the end of the line
+        if pos < line.len() || !line.ends_with(char::is_whitespace) {
+            return None;
+        }
+
+        // Find the innermost list still open before the cursor, unless it
+        // is on a comment or string
+        let tokens = tokenize(line);
+        if tokens.last()?.kind != TokenKind::Whitespace {
+            return None;
+        }
+
+        let mut open = vec![];
+        for (i, token) in tokens.iter().enumerate() {
+            match token.kind {
+                TokenKind::Open(_) => open.push(i),
+                TokenKind::Close(_) => {
+                    open.pop();
+                }
+                _ => {}
+            }
+        }
+
+        let start = *open.last()?;
+        if tokens[start].kind != TokenKind::Open('(') {
+            return None;
+        }
+
+        let operator = tokens.get(start + 1)?;
+        if operator.kind != TokenKind::Symbol {
+            return None;
+        }
+
+        let signature = self
+            .vm
+            .borrow()
+            .function_signature(&line[operator.span.clone()])?;
+        Some(SignatureHint(signature))
     }
 }
 
-#[derive(Completer, Helper, Highlighter, Hinter)]
-struct MajInputValidator {}
+impl Highlighter for MajInputValidator {
+    fn highlight<'l>(&self, line: &'l str, pos: usize) -> Cow<'l, str> {
+        let tokens = tokenize(line);
+
+        // Delimiter under the cursor, or right before it, and its match
+        let is_delimiter = |at: usize| {
+            tokens.iter().any(|token| {
+                token.span.start == at
+                    && matches!(token.kind, TokenKind::Open(_) | TokenKind::Close(_))
+            })
+        };
+        let cursor = [Some(pos), pos.checked_sub(1)]
+            .into_iter()
+            .flatten()
+            .find(|at| is_delimiter(*at));
+        let matched: Vec<usize> = match cursor.and_then(|at| matching_delimiter(&tokens, at)) {
+            Some(other) => vec![cursor.unwrap_or_default(), other],
+            None => vec![],
+        };
+
+        let mut highlighted = String::with_capacity(line.len());
+        for token in &tokens {
+            let text = &line[token.span.clone()];
+            let colored = match token.kind {
+                TokenKind::Open(_) | TokenKind::Close(_) if matched.contains(&token.span.start) => {
+                    text.bright_blue().bold()
+                }
+                TokenKind::Number => text.yellow(),
+                TokenKind::String { .. } | TokenKind::Char => text.green(),
+                TokenKind::Comment => text.bright_black(),
+                TokenKind::Prefix => text.magenta(),
+                TokenKind::Symbol if SPECIAL_FORMS.contains(&text) => text.cyan().bold(),
+                _ => text.normal(),
+            };
+            highlighted.push_str(&colored.to_string());
+        }
+
+        Cow::Owned(highlighted)
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(hint.dimmed().to_string())
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
+        // Refresh on every change, so that delimiter matching follows the cursor
+        true
+    }
+}
 
 impl Validator for MajInputValidator {
     fn validate(&self, ctx: &mut ValidationContext) -> Result<ValidationResult, ReadlineError> {
         use ValidationResult::{Incomplete, Invalid, Valid};
 
         let input = ctx.input();
-        let mut ignore_one = false;
-        let mut count = 0;
-        for c in input.chars() {
-            if ignore_one {
-                ignore_one = false;
-            } else {
-                match c {
-                    '(' => {
-                        count += 1;
-                    }
-                    ')' => {
-                        if count == 0 {
-                            count = -1;
-                            break;
-                        } else {
-                            count -= 1;
-                        }
-                    }
-                    '\\' => {
-                        // Gambiarra for preventing
-                        // incomplete input on characters
-                        // such as #\( and #\)
-                        ignore_one = true;
-                    }
-                    _ => {}
-                }
-            }
+        if command_p(input) {
+            return Ok(Valid(None));
         }
 
-        if count > 0 {
-            Ok(Incomplete)
-        } else if count < 0 {
-            Ok(Invalid(Some("No matching parenthesis found".to_owned())))
-        } else {
-            Ok(Valid(None))
+        match balance(&tokenize(input)) {
+            Balance::Complete => Ok(Valid(None)),
+            Balance::Incomplete => Ok(Incomplete),
+            Balance::Unexpected {
+                found,
+                expected,
+                span,
+            } => Ok(Invalid(Some(delimiter_report(
+                input, found, expected, span,
+            )))),
         }
     }
 }
 
-fn repl(mut vm: &mut VirtualMachine) {
+/// Renders the report for a stray closing delimiter, starting on a new line
+/// so that it is shown below the input.
+fn delimiter_report(
+    input: &str,
+    found: char,
+    expected: Option<char>,
+    span: std::ops::Range<usize>,
+) -> String {
+    use ariadne::{sources, Color, Fmt, Label, Report, ReportKind};
+
+    // Reports are located by character, the tokenizer works with bytes
+    let start = input[..span.start].chars().count();
+    let span = start..start + 1;
+
+    let label = match expected {
+        Some(expected) => format!(
+            "Expected {} but found {}",
+            expected.fg(Color::Yellow),
+            found.fg(Color::Red)
+        ),
+        None => format!("Unmatched {}", found.fg(Color::Red)),
+    };
+
+    let mut output = vec![b'\n'];
+    let _ = Report::build(ReportKind::Error, "<repl>", start)
+        .with_message("Unexpected closing delimiter")
+        .with_label(
+            Label::new(("<repl>", span))
+                .with_message(label)
+                .with_color(Color::Red),
+        )
+        .finish()
+        .write(sources(vec![("<repl>", input.to_owned())]), &mut output);
+
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+fn repl(vm: Box<VirtualMachine>, prelude: bool) {
     let history_path = {
         let mut path = std::env::current_dir().unwrap();
         path.push(".majestic_history");
         path
     };
 
-    let validator = MajInputValidator {};
+    let shared = Rc::new(RefCell::new(vm));
+    let validator = MajInputValidator { vm: shared.clone() };
 
     let config = rustyline::Config::builder()
         .history_ignore_space(true)
@@ -99,59 +455,91 @@ fn repl(mut vm: &mut VirtualMachine) {
     let prompt = format!("{}", "> ".green());
     let prompt_dbg = format!("{}", "> ".red());
 
-    let mut ast = false;
-    let mut echo = false;
+    let mut state = ReplState {
+        ast: false,
+        echo: false,
+        time: false,
+        pretty: false,
+        prelude,
+        quit: false,
+    };
 
-    println!("Press C-c or C-d to quit");
-    loop {
-        let readline = rl.readline(if !ast && !echo { &prompt } else { &prompt_dbg });
+    println!("Press C-c or C-d to quit, or type #help for commands");
+    while !state.quit {
+        let debug = state.ast || state.echo;
+        let readline = rl.readline(if !debug { &prompt } else { &prompt_dbg });
+        let mut guard = shared.borrow_mut();
         match readline {
-            Ok(line) if line.trim() == "#debrief" => vm.debrief(),
-            Ok(line) if line.trim() == "#atom" => vm.print_atom_table(),
-            Ok(line) if line.trim() == "#number" => vm.print_number_table(),
-            Ok(line) if line.trim() == "#list" => vm.print_list_area(),
-            Ok(line) if line.trim() == "#reg" => vm.print_registers(),
-            Ok(line) if line.trim() == "#ast" => ast = !ast,
-            Ok(line) if line.trim() == "#echo" => echo = !echo,
-            Ok(line) if line.trim().starts_with("#env") => match line.trim()[4..].trim().parse() {
-                Ok(num) => vm.print_env(num),
-                Err(_) => println!("Could not parse environment number"),
-            },
-            Ok(line) if (line.trim().len() > 0) && line.trim().get(0..1).unwrap() == "#" => {
-                eprintln!("Unknown command {}.", line.trim())
+            Ok(line) if command_p(&line) => {
+                rl.add_history_entry(line.trim());
+                let (name, args) = line.trim()[1..]
+                    .split_once(' ')
+                    .unwrap_or((&line.trim()[1..], ""));
+                match find_command(name) {
+                    Some(command) => (command.run)(&mut guard, &mut state, args.trim()),
+                    None => eprintln!(
+                        "Unknown command #{}. Type #help for a list of commands.",
+                        name
+                    ),
+                }
             }
             Ok(line) => {
+                let vm: &mut VirtualMachine = &mut guard;
                 use chumsky::Parser;
                 rl.add_history_entry(line.clone().trim());
 
+                vm.replace_source("<repl>", line.trim());
                 let (maj, errs) = Combinators::parser().parse_recovery(line.trim());
-                report_error(line, errs);
+                report_error("<repl>", line.trim().to_owned(), errs);
 
-                if ast {
+                if state.ast {
                     println!("{}", format!("{:#?}", maj).cyan());
                 }
 
                 if let Some(expressions) = maj.clone() {
                     for expr in expressions {
-                        match parser::convert::build_ast(&mut vm, expr) {
+                        match parser::convert::build_ast(vm, expr) {
                             Err(e) => eprintln!("Error while converting to S-expression: {}", e),
                             Ok(ptr) => {
-                                if ast {
+                                if state.ast {
                                     println!("{}", format!("{}", ptr).magenta());
                                 }
 
-                                if echo {
+                                if state.echo {
                                     print!("echo: ");
-                                    printer::print_object(&vm, &ptr);
+                                    printer::print_object(vm, &ptr);
                                     println!();
                                 }
 
-                                match vm.evaluate(ptr) {
+                                let start = std::time::Instant::now();
+                                let result = vm.evaluate(ptr);
+                                if state.time {
+                                    let elapsed = format!("Evaluation took {:?}", start.elapsed());
+                                    eprintln!("{}", elapsed.dimmed());
+                                }
+
+                                match result {
                                     Ok(ret) => {
-                                        printer::print_object(&vm, &ret);
+                                        if state.pretty {
+                                            printer::print_pretty(vm, &ret);
+                                        } else {
+                                            printer::print_object(vm, &ret);
+                                        }
                                         println!();
                                     }
-                                    Err(e) => eprintln!("Error during evaluation: {}", e),
+                                    Err(e) => {
+                                        report_runtime_error(vm, &e);
+                                        if e.discarded() > 0 {
+                                            eprintln!(
+                                                "{}",
+                                                format!(
+                                                    "Recovered: {} stack entries discarded",
+                                                    e.discarded()
+                                                )
+                                                .dimmed()
+                                            );
+                                        }
+                                    }
                                 }
                             }
                         }
@@ -184,8 +572,8 @@ fn repl(mut vm: &mut VirtualMachine) {
     println!("Quaerendo invenietis.");
 }
 
-fn report_error(src: String, errs: Vec<chumsky::prelude::Simple<char>>) {
-    use ariadne::{Color, Fmt, Label, Report, ReportKind, Source};
+fn report_error(name: &str, src: String, errs: Vec<chumsky::prelude::Simple<char>>) {
+    use ariadne::{sources, Color, Fmt, Label, Report, ReportKind};
 
     errs.into_iter().for_each(|e| {
         let msg = if let chumsky::error::SimpleReason::Custom(msg) = e.reason() {
@@ -217,11 +605,11 @@ fn report_error(src: String, errs: Vec<chumsky::prelude::Simple<char>>) {
             )
         };
 
-        let report = Report::build(ReportKind::Error, (), e.span().start)
+        let report = Report::build(ReportKind::Error, name.to_owned(), e.span().start)
             .with_code(3)
             .with_message(msg)
             .with_label(
-                Label::new(e.span())
+                Label::new((name.to_owned(), e.span()))
                     .with_message(match e.reason() {
                         chumsky::error::SimpleReason::Custom(msg) => msg.clone(),
                         _ => format!(
@@ -236,7 +624,7 @@ fn report_error(src: String, errs: Vec<chumsky::prelude::Simple<char>>) {
 
         let report = match e.reason() {
             chumsky::error::SimpleReason::Unclosed { span, delimiter } => report.with_label(
-                Label::new(span.clone())
+                Label::new((name.to_owned(), span.clone()))
                     .with_message(format!(
                         "Unclosed delimiter {}",
                         delimiter.fg(Color::Yellow)
@@ -247,29 +635,197 @@ fn report_error(src: String, errs: Vec<chumsky::prelude::Simple<char>>) {
             chumsky::error::SimpleReason::Custom(_) => report,
         };
 
-        report.finish().print(Source::from(&src)).unwrap();
+        report
+            .finish()
+            .eprint(sources(vec![(name.to_owned(), src.clone())]))
+            .unwrap();
     });
 }
 
+fn report_runtime_error(vm: &VirtualMachine, e: &vm::error::LispError) {
+    use ariadne::{sources, Color, Label, Report, ReportKind};
+
+    // Loaded files which do not parse are reported like top-level parse
+    // errors, then the load itself is reported
+    if let Some((file, errs)) = e.syntax_errors() {
+        if let Some(source) = vm.get_source(file) {
+            report_error(&source.name, source.text.clone(), errs.to_vec());
+        }
+    }
+
+    let location = vm
+        .error_location(e)
+        .and_then(|location| Some((location, vm.get_source(location.file)?)));
+
+    match location {
+        Some((location, source)) => {
+            let name = source.name.clone();
+            Report::build(ReportKind::Error, name.clone(), location.span.start)
+                .with_message(format!("Error during evaluation: {}", e.message()))
+                .with_label(
+                    Label::new((name.clone(), location.span.clone()))
+                        .with_message("while evaluating this expression")
+                        .with_color(Color::Red),
+                )
+                .finish()
+                .eprint(sources(vec![(name, source.text.clone())]))
+                .unwrap();
+        }
+        None => eprintln!("Error during evaluation: {}", e.message()),
+    }
+
+    if !e.backtrace().is_empty() {
+        vm.print_backtrace(e.backtrace());
+    }
+}
+
+/// Runs a script file, returning the exit status of the process.
+fn run_script(vm: &mut VirtualMachine, file: &str) -> i32 {
+    match std::fs::read_to_string(file) {
+        Ok(text) => run_program(vm, file, &text, false),
+        Err(e) => {
+            eprintln!("Could not read {}: {}", file, e);
+            EXIT_IO_ERROR
+        }
+    }
+}
+
+/// Runs a whole program read from standard input, printing the value of
+/// each expression.
+fn run_stdin(vm: &mut VirtualMachine) -> i32 {
+    use std::io::Read;
+
+    let mut text = String::new();
+    match std::io::stdin().read_to_string(&mut text) {
+        Ok(_) => run_program(vm, "<stdin>", &text, true),
+        Err(e) => {
+            eprintln!("Could not read standard input: {}", e);
+            EXIT_IO_ERROR
+        }
+    }
+}
+
+/// Parses and evaluates every expression of a program, stopping at the
+/// first error. Returns the exit status of the process.
+fn run_program(vm: &mut VirtualMachine, name: &str, text: &str, print: bool) -> i32 {
+    use chumsky::Parser;
+
+    let (maj, errs) = Combinators::parser().parse_recovery(text);
+    if !errs.is_empty() {
+        report_error(name, text.to_owned(), errs);
+        return EXIT_PARSE_ERROR;
+    }
+
+    vm.add_source(name, text);
+    for expr in maj.unwrap_or_default() {
+        match parser::convert::build_ast(vm, expr).and_then(|ptr| vm.evaluate(ptr)) {
+            Ok(ret) => {
+                if print {
+                    printer::print_object(vm, &ret);
+                    println!();
+                }
+            }
+            Err(e) => {
+                report_runtime_error(vm, &e);
+                return EXIT_RUNTIME_ERROR;
+            }
+        }
+    }
+
+    0
+}
+
+/// Creates a virtual machine, evaluating the prelude on it if asked to.
+fn new_vm(prelude: bool) -> vm::error::LispResult<Box<VirtualMachine>> {
+    if prelude {
+        VirtualMachine::with_prelude()
+    } else {
+        Ok(VirtualMachine::new())
+    }
+}
+
+fn print_usage() {
+    eprintln!("Usage: majestic [--no-prelude] [-e EXPR]... [FILE | -] [ARGS...]");
+}
+
 fn main() {
-    load_log_config();
+    // majestic [--no-prelude] [-e EXPR]... [FILE | -] [ARGS...]
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut prelude = true;
+    let mut evals = vec![];
+    let mut index = 0;
+    while let Some(arg) = args.get(index) {
+        match arg.as_str() {
+            "--no-prelude" => prelude = false,
+            "-e" | "--eval" => match args.get(index + 1) {
+                Some(expr) => {
+                    evals.push(expr.clone());
+                    index += 1;
+                }
+                None => {
+                    eprintln!("Missing expression for {}", arg);
+                    print_usage();
+                    std::process::exit(EXIT_USAGE_ERROR);
+                }
+            },
+            _ => break,
+        }
+        index += 1;
+    }
+    let args = &args[index..];
 
-    let version = format!(
-        "{}{}",
-        env!("CARGO_PKG_VERSION"),
-        if VERSION != "" {
-            " (nightly build)"
-        } else {
-            ""
+    let mut vm = match new_vm(prelude) {
+        Ok(vm) => vm,
+        Err(e) => {
+            eprintln!("Could not load the prelude: {}", e);
+            std::process::exit(EXIT_RUNTIME_ERROR);
         }
-    );
-    println!("Majestic Lisp Refactored v{} {}", version, TARGET);
-    if VERSION != "" {
-        println!("Build {} {}", VERSION, TIMESTAMP);
+    };
+
+    if !evals.is_empty() {
+        vm.set_argv(args).expect("Bind *argv*");
+        for expr in evals {
+            let status = run_program(&mut vm, "<eval>", &expr, true);
+            if status != 0 {
+                std::process::exit(status);
+            }
+        }
+        return;
     }
-    println!("Copyright (c) 2020-2023 Lucas S. Vieira");
 
-    let mut vm = VirtualMachine::new();
+    match args.split_first() {
+        Some((file, script_args)) if file == "-" => {
+            vm.set_argv(script_args).expect("Bind *argv*");
+            std::process::exit(run_stdin(&mut vm));
+        }
+        Some((file, script_args)) => {
+            vm.set_argv(script_args).expect("Bind *argv*");
+            std::process::exit(run_script(&mut vm, file));
+        }
+        None if !std::io::stdin().is_terminal() => {
+            vm.set_argv(&[]).expect("Bind *argv*");
+            std::process::exit(run_stdin(&mut vm));
+        }
+        None => {
+            load_log_config();
+            vm.set_argv(&[]).expect("Bind *argv*");
+
+            let version = format!(
+                "{}{}",
+                env!("CARGO_PKG_VERSION"),
+                if !VERSION.is_empty() {
+                    " (nightly build)"
+                } else {
+                    ""
+                }
+            );
+            println!("Majestic Lisp Refactored v{} {}", version, TARGET);
+            if !VERSION.is_empty() {
+                println!("Build {} {}", VERSION, TIMESTAMP);
+            }
+            println!("Copyright (c) 2020-2023 Lucas S. Vieira");
 
-    repl(&mut vm);
+            repl(vm, prelude);
+        }
+    }
 }
@@ -69,6 +69,17 @@ impl Combinators {
             .map(Some)
             .chain::<char, _, _>(filter(valid_char).repeated())
             .collect::<String>()
+            // `#` followed by a digit starts a datum label
+            .try_map(|name, span| {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some('#'), Some(c)) if c.is_ascii_digit() => Err(Simple::custom(
+                        span,
+                        format!("invalid datum label {}", name),
+                    )),
+                    _ => Ok(name),
+                }
+            })
             .map(Expr::make_symbol)
             .labelled("symbol")
     }
@@ -89,8 +100,9 @@ impl Combinators {
     }
 
     pub fn comment() -> impl Parser<char, Expr, Error = Simple<char>> {
+        // A comment on the last line of input may end without a newline
         just(";")
-            .ignore_then(take_until(just('\n')))
+            .ignore_then(take_until(just('\n').ignored().or(end())))
             .map(|pair| {
                 let value: String = pair.0.iter().cloned().collect::<String>();
                 Expr::Comment(value)
@@ -109,6 +121,7 @@ impl Combinators {
                 .delimited_by(just('('), just(')'))
                 .collect::<Vec<Expr>>()
                 .map(Expr::List)
+                .map_with_span(Expr::make_spanned)
                 .labelled("list");
 
             let dotted_list = expression
@@ -121,6 +134,7 @@ impl Combinators {
                 .delimited_by(just('('), just(')'))
                 .collect::<Vec<Expr>>()
                 .map(Expr::DottedList)
+                .map_with_span(Expr::make_spanned)
                 .labelled("dotted list");
 
             let cons = expression
@@ -131,6 +145,7 @@ impl Combinators {
                 .delimited_by(just('('), just(')'))
                 .collect::<Vec<Expr>>()
                 .map(Expr::make_cons)
+                .map_with_span(Expr::make_spanned)
                 .labelled("cons");
 
             let vector = expression
@@ -140,29 +155,60 @@ impl Combinators {
                 .delimited_by(just('['), just(']'))
                 .collect::<Vec<Expr>>()
                 .map(Expr::Vector)
+                .map_with_span(Expr::make_spanned)
                 .labelled("vector");
 
             let quoted = just('\'')
                 .ignore_then(expression.clone())
                 .map(|e| Expr::Prefixed(PrefixType::Quote, Box::new(e)))
+                .map_with_span(Expr::make_spanned)
                 .labelled("quoted expression");
 
             let quasiquoted = just('`')
                 .ignore_then(expression.clone())
                 .map(|e| Expr::Prefixed(PrefixType::Quasiquote, Box::new(e)))
+                .map_with_span(Expr::make_spanned)
                 .labelled("quasiquoted expression");
 
             let unquoted_splice = just(",@")
                 .ignore_then(expression.clone())
                 .map(|e| Expr::Prefixed(PrefixType::UnquoteSplice, Box::new(e)))
+                .map_with_span(Expr::make_spanned)
                 .labelled("unquoted expression");
 
             let unquoted = just(',')
                 .ignore_then(expression.clone())
                 .map(|e| Expr::Prefixed(PrefixType::Unquote, Box::new(e)))
+                .map_with_span(Expr::make_spanned)
                 .labelled("unquoted expression");
 
+            // Datum labels, for shared and circular structure. Label numbers
+            // which do not fit are parse errors
+            let label_number = || {
+                text::int(10).try_map(|n: String, span| {
+                    n.parse::<usize>().map_err(|_| {
+                        Simple::custom(span, format!("datum label {} is too large", n))
+                    })
+                })
+            };
+
+            let label = just('#')
+                .ignore_then(label_number())
+                .then_ignore(just('='))
+                .then(expression.clone())
+                .map(|(n, e)| Expr::Label(n, Box::new(e)))
+                .map_with_span(Expr::make_spanned)
+                .labelled("datum label");
+
+            let label_ref = just('#')
+                .ignore_then(label_number())
+                .then_ignore(just('#'))
+                .map(Expr::LabelRef)
+                .labelled("datum label reference");
+
             Self::comment()
+                .or(label)
+                .or(label_ref)
                 .or(quoted)
                 .or(quasiquoted)
                 .or(unquoted_splice)
@@ -178,7 +224,17 @@ impl Combinators {
 
     // == ACTUAL PARSER ==
 
+    /// Parses every expression of a text. Comments between them are not
+    /// forms, so they are left out.
     pub fn parser() -> impl Parser<char, Vec<Expr>, Error = Simple<char>> {
-        Self::expression().repeated().then_ignore(end())
+        Self::expression()
+            .repeated()
+            .then_ignore(end())
+            .map(|exprs| {
+                exprs
+                    .into_iter()
+                    .filter(|expr| !matches!(expr, Expr::Comment(_)))
+                    .collect()
+            })
     }
 }
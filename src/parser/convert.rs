@@ -1,23 +1,95 @@
 use super::expression::*;
 use crate::vm::error::*;
 use crate::vm::*;
+use std::collections::HashMap;
+
+/// Objects defined by datum labels, `#n=`, on the expression being built.
+type Labels = HashMap<usize, TypedPointer>;
 
 pub fn build_ast(vm: &mut VirtualMachine, expr: Expr) -> LispResult<TypedPointer> {
+    build_labelled_ast(vm, expr, &mut Labels::new())
+}
+
+fn build_labelled_ast(
+    vm: &mut VirtualMachine,
+    expr: Expr,
+    labels: &mut Labels,
+) -> LispResult<TypedPointer> {
     match expr {
         Expr::Atom(atom_expr) => build_atom_ast(vm, atom_expr),
-        Expr::Prefixed(prefix, boxed_expr) => build_prefixed_ast(vm, prefix, *boxed_expr),
-        Expr::List(exprs) => build_list_ast(vm, exprs),
-        Expr::DottedList(exprs) => build_dotted_list_ast(vm, exprs),
+        Expr::Prefixed(prefix, boxed_expr) => {
+            build_list_ast(vm, vec![prefix_symbol(prefix), *boxed_expr], labels)
+        }
+        Expr::List(exprs) => build_list_ast(vm, exprs, labels),
+        Expr::DottedList(exprs) => build_dotted_list_ast(vm, exprs, labels),
         Expr::Vector(_exprs) => Err(LispError::internal("vector storage not implemented")),
-        Expr::Cons(boxed_car, boxed_cdr) => build_cons_ast(vm, *boxed_car, *boxed_cdr),
+        Expr::Cons(boxed_car, boxed_cdr) => build_cons_ast(vm, *boxed_car, *boxed_cdr, labels),
+        Expr::Spanned(boxed_expr, span) => {
+            let ptr = build_labelled_ast(vm, *boxed_expr, labels)?;
+            vm.set_location(&ptr, span);
+            Ok(ptr)
+        }
+        Expr::Label(n, boxed_expr) => build_label_ast(vm, n, *boxed_expr, labels),
+        Expr::LabelRef(n) => labels
+            .get(&n)
+            .cloned()
+            .ok_or_else(|| LispError::read(format!("undefined datum label #{}#", n))),
         _ => vm.make_atom("nil"),
     }
 }
 
+/// Builds the object of a datum label. References to the label inside the
+/// object are read as a placeholder cons, which then takes the contents of
+/// the object, so that circular structure is rebuilt. An object which is
+/// nothing but a reference to a label being built, as in `#1=#1#`, has no
+/// contents to take and cannot be read.
+fn build_label_ast(
+    vm: &mut VirtualMachine,
+    n: usize,
+    expr: Expr,
+    labels: &mut Labels,
+) -> LispResult<TypedPointer> {
+    let placeholder = vm.make_cons()?;
+    labels.insert(n, placeholder.clone());
+
+    let ptr = build_labelled_ast(vm, expr, labels)?;
+    if unfinished_p(vm, &ptr)? {
+        return Err(LispError::read(format!(
+            "datum label #{}= refers to an unfinished object",
+            n
+        )));
+    }
+
+    if ptr.tag != DataType::Cons {
+        labels.insert(n, ptr.clone());
+        return Ok(ptr);
+    }
+
+    let car = vm.get_car(&ptr)?;
+    let cdr = vm.get_cdr(&ptr)?;
+    vm.set_car(&placeholder, car)?;
+    vm.set_cdr(&placeholder, cdr)?;
+    vm.move_location(&ptr, &placeholder);
+
+    // Labels on the object itself, as in `#1=#2=(a)`, are the same object
+    for value in labels.values_mut() {
+        if *value == ptr {
+            *value = placeholder.clone();
+        }
+    }
+    Ok(placeholder)
+}
+
+// Placeholders of labels still being built are the only conses read with
+// undefined contents
+fn unfinished_p(vm: &VirtualMachine, ptr: &TypedPointer) -> LispResult<bool> {
+    Ok((ptr.tag == DataType::Cons) && (vm.get_car(ptr)?.tag == DataType::Undefined))
+}
+
 fn build_atom_ast(vm: &mut VirtualMachine, atom_expr: AtomExpr) -> LispResult<TypedPointer> {
     match atom_expr {
         AtomExpr::Number(number_expr) => vm.make_number(build_number_ast(number_expr)),
-        AtomExpr::String(_string) => Err(LispError::internal("string storage not implemented")),
+        AtomExpr::String(string) => vm.make_string(&string),
         AtomExpr::Symbol(name) => vm.make_atom(&name),
     }
 }
@@ -33,7 +105,11 @@ fn build_number_ast(expr: NumberExpr) -> Number {
     }
 }
 
-fn build_list_ast(vm: &mut VirtualMachine, exprs: Vec<Expr>) -> LispResult<TypedPointer> {
+fn build_list_ast(
+    vm: &mut VirtualMachine,
+    exprs: Vec<Expr>,
+    labels: &mut Labels,
+) -> LispResult<TypedPointer> {
     if exprs.is_empty() {
         return Ok(ConstSymbol::NIL);
     }
@@ -41,7 +117,7 @@ fn build_list_ast(vm: &mut VirtualMachine, exprs: Vec<Expr>) -> LispResult<Typed
     let first = vm.make_cons()?;
     let mut iter = first.clone();
     for (i, expr) in exprs.iter().enumerate() {
-        let ptr = build_ast(vm, expr.clone())?;
+        let ptr = build_labelled_ast(vm, expr.clone(), labels)?;
         vm.set_car(&iter, ptr)?;
 
         if i == exprs.len() - 1 {
@@ -56,7 +132,11 @@ fn build_list_ast(vm: &mut VirtualMachine, exprs: Vec<Expr>) -> LispResult<Typed
     Ok(first)
 }
 
-fn build_dotted_list_ast(vm: &mut VirtualMachine, exprs: Vec<Expr>) -> LispResult<TypedPointer> {
+fn build_dotted_list_ast(
+    vm: &mut VirtualMachine,
+    exprs: Vec<Expr>,
+    labels: &mut Labels,
+) -> LispResult<TypedPointer> {
     if exprs.is_empty() {
         // probably a weird syntax error that slipped through the cracks,
         // if this code is reached
@@ -72,7 +152,7 @@ fn build_dotted_list_ast(vm: &mut VirtualMachine, exprs: Vec<Expr>) -> LispResul
     let first = vm.make_cons()?;
     let mut iter = first.clone();
     for (i, expr) in exprs.iter().enumerate() {
-        let ptr = build_ast(vm, expr.clone())?;
+        let ptr = build_labelled_ast(vm, expr.clone(), labels)?;
 
         if i == exprs.len() - 1 {
             // If we're at the end, assign to previous cdr
@@ -94,9 +174,10 @@ fn build_cons_ast(
     vm: &mut VirtualMachine,
     car_expr: Expr,
     cdr_expr: Expr,
+    labels: &mut Labels,
 ) -> LispResult<TypedPointer> {
-    let car = build_ast(vm, car_expr)?;
-    let cdr = build_ast(vm, cdr_expr)?;
+    let car = build_labelled_ast(vm, car_expr, labels)?;
+    let cdr = build_labelled_ast(vm, cdr_expr, labels)?;
 
     let cons = vm.make_cons()?;
     vm.set_car(&cons, car)?;
@@ -110,7 +191,11 @@ pub fn build_prefixed_ast(
     prefix: PrefixType,
     expr: Expr,
 ) -> LispResult<TypedPointer> {
-    let prefix_expr = Expr::make_symbol(
+    build_list_ast(vm, vec![prefix_symbol(prefix), expr], &mut Labels::new())
+}
+
+fn prefix_symbol(prefix: PrefixType) -> Expr {
+    Expr::make_symbol(
         match prefix {
             PrefixType::Quote => "quote",
             PrefixType::Quasiquote => "quasiquote",
@@ -118,7 +203,5 @@ pub fn build_prefixed_ast(
             PrefixType::UnquoteSplice => "unquote-splice",
         }
         .to_owned(),
-    );
-
-    build_list_ast(vm, vec![prefix_expr, expr])
+    )
 }
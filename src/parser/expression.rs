@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 pub const RESERVED: &[char] = &['(', ')', '[', ']', '\'', '`', ',', '"', '@', '.'];
 
 #[derive(Debug, Clone, PartialEq)]
@@ -23,7 +25,7 @@ pub enum PrefixType {
     UnquoteSplice,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum Expr {
     Atom(AtomExpr),
     Prefixed(PrefixType, Box<Expr>),
@@ -32,9 +34,44 @@ pub enum Expr {
     Vector(Vec<Expr>),
     Cons(Box<Expr>, Box<Expr>),
     Comment(String),
+    Spanned(Box<Expr>, Range<usize>),
+    /// Datum label definition, `#n=expr`.
+    Label(usize, Box<Expr>),
+    /// Reference to a datum label, `#n#`.
+    LabelRef(usize),
+}
+
+// Spans are source information only, so they are ignored when comparing
+// expressions.
+impl PartialEq for Expr {
+    fn eq(&self, other: &Self) -> bool {
+        use Expr::*;
+        match (self.unspanned(), other.unspanned()) {
+            (Atom(a), Atom(b)) => a == b,
+            (Prefixed(pa, a), Prefixed(pb, b)) => (pa == pb) && (a == b),
+            (List(a), List(b)) | (DottedList(a), DottedList(b)) | (Vector(a), Vector(b)) => a == b,
+            (Cons(a1, a2), Cons(b1, b2)) => (a1 == b1) && (a2 == b2),
+            (Comment(a), Comment(b)) => a == b,
+            (Label(na, a), Label(nb, b)) => (na == nb) && (a == b),
+            (LabelRef(a), LabelRef(b)) => a == b,
+            _ => false,
+        }
+    }
 }
 
 impl Expr {
+    /// Expression without its span information.
+    pub fn unspanned(&self) -> &Expr {
+        match self {
+            Expr::Spanned(expr, _) => expr.unspanned(),
+            expr => expr,
+        }
+    }
+
+    pub fn make_spanned(expr: Expr, span: Range<usize>) -> Expr {
+        Expr::Spanned(Box::new(expr), span)
+    }
+
     pub fn make_integer(s: String) -> Expr {
         Expr::Atom(AtomExpr::Number(NumberExpr::Integer(s.parse().unwrap())))
     }
@@ -55,7 +92,7 @@ impl Expr {
     }
 
     pub fn make_complex(v: Vec<Expr>) -> Expr {
-        let first = v.get(0).unwrap();
+        let first = v.first().unwrap();
         let second = v.get(1).unwrap();
 
         if let (Expr::Atom(AtomExpr::Number(real)), Expr::Atom(AtomExpr::Number(imag))) =
@@ -80,7 +117,7 @@ impl Expr {
 
     pub fn make_cons(v: Vec<Expr>) -> Expr {
         Expr::Cons(
-            Box::new(v.get(0).unwrap().clone()),
+            Box::new(v.first().unwrap().clone()),
             Box::new(v.get(1).unwrap().clone()),
         )
     }
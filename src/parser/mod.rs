@@ -1,6 +1,7 @@
 pub mod combinators;
 pub mod convert;
 pub mod expression;
+pub mod tokens;
 
 #[cfg(test)]
 mod test;
@@ -1,3 +1,6 @@
+// 3.14 is a parser input here, not an approximation of pi
+#![allow(clippy::approx_constant, clippy::useless_format)]
+
 use crate::parser::combinators::Combinators;
 use crate::parser::expression::*;
 use chumsky::prelude::*;
@@ -216,6 +219,7 @@ fn parse_comment() {
 
     assert!(parser.parse("; this is a comment\n").is_ok());
     assert!(parser.parse(";; this is another comment\n").is_ok());
+    assert!(parser.parse("; a comment at the end of input").is_ok());
 }
 
 // expressions -- lists
@@ -542,6 +546,38 @@ fn parse_expression_quasiquote_unquote() {
     assert!(parser.parse(",@").is_err());
 }
 
+// expressions -- datum labels
+#[test]
+fn parse_datum_labels() {
+    let parser = Combinators::expression().then_ignore(end());
+
+    let symbol_helper = |v: &str| Expr::Atom(AtomExpr::Symbol(v.to_owned()));
+    let label_helper = |n, e| Expr::Label(n, Box::new(e));
+
+    assert_eq!(
+        Ok(label_helper(
+            1,
+            Expr::make_cons(vec![symbol_helper("a"), Expr::LabelRef(1)])
+        )),
+        parser.parse("#1=(a . #1#)")
+    );
+    assert_eq!(
+        Ok(Expr::List(vec![
+            label_helper(2, Expr::List(vec![symbol_helper("x")])),
+            Expr::LabelRef(2)
+        ])),
+        parser.parse("(#2=(x) #2#)")
+    );
+
+    // Not a label, but a symbol
+    assert_eq!(Ok(symbol_helper("#foo")), parser.parse("#foo"));
+
+    // Label numbers which do not fit are errors
+    assert!(parser.parse("#99999999999999999999999=(a)").is_err());
+    assert!(parser.parse("(a #99999999999999999999999#)").is_err());
+    assert!(parser.parse("#1").is_err());
+}
+
 #[test]
 fn parse_many_expressions() {
     let parser = Combinators::parser();
@@ -549,4 +585,112 @@ fn parse_many_expressions() {
     assert!(parser
         .parse("1 2 3 test foo '(foo bar) [5 6] `(foo ,(eval bar) ,@(eval '(list '+ 1 2)))")
         .is_ok());
+
+    // Comments are not expressions, even when no newline ends them
+    assert_eq!(
+        parser.parse("; first\n1 ; second"),
+        Ok(vec![Expr::Atom(AtomExpr::Number(NumberExpr::Integer(1)))])
+    );
+}
+
+// tokenizer
+#[test]
+fn tokenize_text() {
+    use crate::parser::tokens::{tokenize, TokenKind};
+
+    let kinds = |text: &str| -> Vec<TokenKind> {
+        tokenize(text)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .map(|token| token.kind)
+            .collect()
+    };
+
+    assert_eq!(
+        kinds("'(car [1 -2.5])"),
+        vec![
+            TokenKind::Prefix,
+            TokenKind::Open('('),
+            TokenKind::Symbol,
+            TokenKind::Open('['),
+            TokenKind::Number,
+            TokenKind::Number,
+            TokenKind::Close(']'),
+            TokenKind::Close(')'),
+        ]
+    );
+
+    // Delimiters inside strings, comments and characters are not tokens
+    assert_eq!(
+        kinds("(f \"(\" #\\) ; )\n)"),
+        vec![
+            TokenKind::Open('('),
+            TokenKind::Symbol,
+            TokenKind::String { terminated: true },
+            TokenKind::Char,
+            TokenKind::Comment,
+            TokenKind::Close(')'),
+        ]
+    );
+
+    assert_eq!(
+        kinds(",@x \"open"),
+        vec![
+            TokenKind::Prefix,
+            TokenKind::Symbol,
+            TokenKind::String { terminated: false },
+        ]
+    );
+
+    // Tokens cover the whole text
+    let text = "(défn  λ (x) \"ü\")";
+    let tokens = tokenize(text);
+    assert_eq!(tokens.first().map(|t| t.span.start), Some(0));
+    assert_eq!(tokens.last().map(|t| t.span.end), Some(text.len()));
+    assert!(tokens.windows(2).all(|w| w[0].span.end == w[1].span.start));
+}
+
+#[test]
+fn match_delimiters() {
+    use crate::parser::tokens::{matching_delimiter, tokenize};
+
+    let text = "(a [b \")\"] (c))";
+    let tokens = tokenize(text);
+
+    assert_eq!(matching_delimiter(&tokens, 0), Some(14));
+    assert_eq!(matching_delimiter(&tokens, 14), Some(0));
+    assert_eq!(matching_delimiter(&tokens, 3), Some(9));
+    assert_eq!(matching_delimiter(&tokens, 11), Some(13));
+    assert_eq!(matching_delimiter(&tokens, 1), None);
+    assert_eq!(matching_delimiter(&tokenize("(a (b)"), 0), None);
+}
+
+#[test]
+fn balance_delimiters() {
+    use crate::parser::tokens::{balance, tokenize, Balance};
+
+    let check = |text: &str| balance(&tokenize(text));
+
+    assert_eq!(check("(car '(1 2)) [a b]"), Balance::Complete);
+    assert_eq!(check("(f \"(\" ; (\n #\\( )"), Balance::Complete);
+    assert_eq!(check("(f (g"), Balance::Incomplete);
+    assert_eq!(check("[1 2"), Balance::Incomplete);
+    assert_eq!(check("(f \"a)"), Balance::Incomplete);
+
+    assert_eq!(
+        check("(a))"),
+        Balance::Unexpected {
+            found: ')',
+            expected: None,
+            span: 3..4
+        }
+    );
+    assert_eq!(
+        check("(a [b)]"),
+        Balance::Unexpected {
+            found: ')',
+            expected: Some(']'),
+            span: 5..6
+        }
+    );
 }
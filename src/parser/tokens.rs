@@ -0,0 +1,190 @@
+//! Lightweight tokenizer for source text. Unlike the parser combinators, it
+//! never fails, so it can be used on incomplete input such as a line being
+//! edited on the REPL.
+
+use std::ops::Range;
+
+/// Kind of a token.
+/// - `Open` / `Close`: List or vector delimiters.
+/// - `Prefix`: Quote, quasiquote, unquote and unquote-splice prefixes.
+/// - `String`: A string literal, possibly missing its closing quote.
+/// - `Char`: A character literal such as `#\(`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Open(char),
+    Close(char),
+    Prefix,
+    Number,
+    String { terminated: bool },
+    Char,
+    Comment,
+    Symbol,
+    Whitespace,
+}
+
+/// Token found on the source text, spanning a byte range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+/// Characters which end a symbol or number.
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || "()[]'`,\";".contains(c)
+}
+
+/// Splits source text into tokens, covering the whole text.
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        // Consumes characters while the predicate holds, returning the end
+        // of the token
+        let take_while = |chars: &mut std::iter::Peekable<std::str::CharIndices>,
+                          pred: &dyn Fn(char) -> bool| {
+            while let Some(&(_, c)) = chars.peek() {
+                if !pred(c) {
+                    break;
+                }
+                chars.next();
+            }
+            chars.peek().map(|&(i, _)| i).unwrap_or(text.len())
+        };
+
+        let (kind, end) = match c {
+            '(' | '[' => (TokenKind::Open(c), start + 1),
+            ')' | ']' => (TokenKind::Close(c), start + 1),
+            '\'' | '`' => (TokenKind::Prefix, start + 1),
+            ',' => {
+                if text[start + 1..].starts_with('@') {
+                    chars.next();
+                    (TokenKind::Prefix, start + 2)
+                } else {
+                    (TokenKind::Prefix, start + 1)
+                }
+            }
+            ';' => (TokenKind::Comment, take_while(&mut chars, &|c| c != '\n')),
+            '"' => {
+                let end = take_while(&mut chars, &|c| c != '"');
+                match chars.next() {
+                    Some(_) => (TokenKind::String { terminated: true }, end + 1),
+                    None => (TokenKind::String { terminated: false }, end),
+                }
+            }
+            '#' if text[start + 1..].starts_with('\\') => {
+                chars.next();
+                match chars.next() {
+                    Some(_) => (
+                        TokenKind::Char,
+                        take_while(&mut chars, &|c| !is_delimiter(c)),
+                    ),
+                    None => (TokenKind::Char, text.len()),
+                }
+            }
+            c if c.is_whitespace() => (
+                TokenKind::Whitespace,
+                take_while(&mut chars, &char::is_whitespace),
+            ),
+            _ => {
+                let end = take_while(&mut chars, &|c| !is_delimiter(c));
+                let digits = text[start..end].trim_start_matches('-');
+                if digits.starts_with(|c: char| c.is_ascii_digit()) {
+                    (TokenKind::Number, end)
+                } else {
+                    (TokenKind::Symbol, end)
+                }
+            }
+        };
+
+        tokens.push(Token {
+            kind,
+            span: start..end,
+        });
+    }
+
+    tokens
+}
+
+/// Finds the delimiter matching the one starting at byte position `pos`,
+/// skipping delimiters inside strings, comments and character literals.
+pub fn matching_delimiter(tokens: &[Token], pos: usize) -> Option<usize> {
+    let index = tokens.iter().position(|token| token.span.start == pos)?;
+
+    match tokens[index].kind {
+        TokenKind::Open(_) => {
+            let mut depth = 0;
+            for token in &tokens[index..] {
+                match token.kind {
+                    TokenKind::Open(_) => depth += 1,
+                    TokenKind::Close(_) => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    return Some(token.span.start);
+                }
+            }
+            None
+        }
+        TokenKind::Close(_) => {
+            let mut depth = 0;
+            for token in tokens[..=index].iter().rev() {
+                match token.kind {
+                    TokenKind::Close(_) => depth += 1,
+                    TokenKind::Open(_) => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    return Some(token.span.start);
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Whether the delimiters of source text are balanced.
+/// - `Incomplete`: A list, vector or string is still open.
+/// - `Unexpected`: A closing delimiter, at the given byte range, does not
+///   close any open delimiter, or closes one of another kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Balance {
+    Complete,
+    Incomplete,
+    Unexpected {
+        found: char,
+        expected: Option<char>,
+        span: Range<usize>,
+    },
+}
+
+/// Checks the balance of delimiters, ignoring those inside strings,
+/// comments and character literals.
+pub fn balance(tokens: &[Token]) -> Balance {
+    let mut open = vec![];
+    for token in tokens {
+        match token.kind {
+            TokenKind::Open(c) => open.push(if c == '(' { ')' } else { ']' }),
+            TokenKind::Close(c) => match open.pop() {
+                Some(expected) if expected == c => {}
+                expected => {
+                    return Balance::Unexpected {
+                        found: c,
+                        expected,
+                        span: token.span.clone(),
+                    }
+                }
+            },
+            TokenKind::String { terminated: false } => return Balance::Incomplete,
+            _ => {}
+        }
+    }
+
+    if open.is_empty() {
+        Balance::Complete
+    } else {
+        Balance::Incomplete
+    }
+}
@@ -1,4 +1,6 @@
+use crate::vm::error::LispResult;
 use crate::vm::*;
+use std::collections::{HashMap, HashSet};
 
 #[cfg(test)]
 mod test;
@@ -8,43 +10,476 @@ pub fn print_object(vm: &VirtualMachine, ptr: &TypedPointer) {
 }
 
 pub fn format_object(vm: &VirtualMachine, ptr: &TypedPointer) -> String {
+    match ptr.tag {
+        DataType::Cons => format_doc(&make_doc(vm, ptr)),
+        _ => format_atom(vm, ptr),
+    }
+}
+
+/// Prints an object laid out over multiple lines, within the width given
+/// by `*print-right-margin*`.
+pub fn print_pretty(vm: &VirtualMachine, ptr: &TypedPointer) {
+    let width = print_limit(vm, ConstSymbol::PRINT_RIGHT_MARGIN).unwrap_or(DEFAULT_WIDTH);
+    print!("{}", format_pretty(vm, ptr, width));
+}
+
+/// Formats an object laid out over multiple lines, so that lines fit on
+/// `width` columns whenever possible. Lists are broken with the usual Lisp
+/// indentation: special forms indent their body by two columns, and
+/// function arguments are aligned under the first one.
+pub fn format_pretty(vm: &VirtualMachine, ptr: &TypedPointer, width: usize) -> String {
+    let doc = make_doc(vm, ptr);
+    let mut out = String::new();
+    layout_doc(&doc, 0, width, &mut out);
+    out
+}
+
+fn format_atom(vm: &VirtualMachine, ptr: &TypedPointer) -> String {
     match ptr.tag {
         DataType::Undefined => "undefined".to_string(),
         DataType::Number => format!("{}", vm.numbers.area[ptr.value]),
         DataType::Atom => vm.atoms.area[ptr.value].name.to_string(),
-        DataType::Function => format!("#<FUNCTION {{{:#08x}}}>", ptr.value),
-        DataType::Literal => format!("#<LITERAL {{{:#08x}}}>", ptr.value),
-        DataType::BuiltInFunction => format!("#<BUILTIN-FUNCTION {{{:#08x}}}>", ptr.value),
+        DataType::Function | DataType::Macro => format_function(vm, ptr),
+        DataType::Literal => format_literal(vm, ptr),
+        DataType::BuiltInFunction => match builtin_name(ptr) {
+            Some(name) => format!("#<BUILTIN {}>", name),
+            None => format!("#<BUILTIN {{{:#08x}}}>", ptr.value),
+        },
         DataType::BuiltInLiteral => format!("#<BUILTIN-LITERAL {{{:#08x}}}>", ptr.value),
         DataType::Environment => format!("#<ENV{}>", ptr.value),
-        DataType::Cons => {
-            let mut s: String = String::new();
-            s.push('(');
-            s.push_str(&format_list(vm, ptr));
-            s
+        DataType::String => format!("\"{}\"", vm.strings.area[ptr.value]),
+        DataType::Cons => format_object(vm, ptr),
+    }
+}
+
+// Closures and macros show their name, if they have one, and their
+// lambda list
+fn format_function(vm: &VirtualMachine, ptr: &TypedPointer) -> String {
+    let kind = if ptr.tag == DataType::Macro {
+        "MACRO"
+    } else {
+        "FUNCTION"
+    };
+
+    let lambda_list = match vm.closure_parts(ptr) {
+        Ok((lambda_list, _, _)) => format_lambda_list(vm, &lambda_list),
+        Err(_) => return format!("#<{} {{{:#08x}}}>", kind, ptr.value),
+    };
+
+    match vm.function_name(ptr) {
+        Some(name) => format!("#<{} {} {}>", kind, format_object(vm, &name), lambda_list),
+        None => format!("#<{} {}>", kind, lambda_list),
+    }
+}
+
+/// Formats a lambda list, showing an empty one as `()`.
+pub fn format_lambda_list(vm: &VirtualMachine, ptr: &TypedPointer) -> String {
+    if *ptr == ConstSymbol::NIL {
+        "()".to_owned()
+    } else {
+        format_object(vm, ptr)
+    }
+}
+
+/// Describes a function: the lambda list, body and captured bindings of
+/// a closure or macro, or the number of arguments of a built-in function.
+pub fn format_description(vm: &VirtualMachine, ptr: &TypedPointer) -> LispResult<String> {
+    let mut s = format!("{}\n", format_object(vm, ptr));
+
+    if ptr.tag == DataType::BuiltInFunction {
+        if let Some(arity) = builtin_arity(ptr) {
+            s.push_str(&format!("Arguments: {}\n", arity));
         }
+        return Ok(s);
     }
+
+    let (lambda_list, body, _) = vm.closure_parts(ptr)?;
+    s.push_str(&format!(
+        "Lambda list: {}\n",
+        format_lambda_list(vm, &lambda_list)
+    ));
+
+    s.push_str("Body:\n");
+    for form in vm.list_to_vec(body)? {
+        s.push_str(&format!("  {}\n", format_object(vm, &form)));
+    }
+
+    let bindings = vm.captured_bindings(ptr)?;
+    if bindings.is_empty() {
+        s.push_str("Environment: global\n");
+    } else {
+        s.push_str("Environment:\n");
+        for (atom, value) in bindings {
+            s.push_str(&format!(
+                "  {} = {}\n",
+                format_object(vm, &atom),
+                format_object(vm, &value)
+            ));
+        }
+    }
+
+    Ok(s)
 }
 
+/// Formats an object for display purposes, so strings are shown without
+/// their quotes.
+pub fn format_plain(vm: &VirtualMachine, ptr: &TypedPointer) -> String {
+    match ptr.tag {
+        DataType::String => vm.strings.area[ptr.value].clone(),
+        _ => format_object(vm, ptr),
+    }
+}
+
+/// Replaces each `{}` on a format string by the next argument, formatted
+/// for display. Extra placeholders are left as they are.
+pub fn format_string(vm: &VirtualMachine, fmt: &str, args: &[TypedPointer]) -> String {
+    let mut args = args.iter();
+    let mut pieces = fmt.split("{}");
+    let mut s = String::from(pieces.next().unwrap_or_default());
+
+    for piece in pieces {
+        match args.next() {
+            Some(arg) => s.push_str(&format_plain(vm, arg)),
+            None => s.push_str("{}"),
+        }
+        s.push_str(piece);
+    }
+
+    s
+}
+
+fn format_literal(vm: &VirtualMachine, ptr: &TypedPointer) -> String {
+    if vm.is_continuation(ptr) {
+        return format!("#<CONTINUATION {{{:#08x}}}>", ptr.value);
+    }
+
+    match vm.error_object_parts(ptr) {
+        Ok((kind, message, _)) => format!(
+            "#<ERROR {}: {}>",
+            format_object(vm, &kind),
+            format_plain(vm, &message)
+        ),
+        Err(_) => format!("#<LITERAL {{{:#08x}}}>", ptr.value),
+    }
+}
+
+/// Formats the elements of a list and its closing parenthesis, without
+/// the opening one or the datum label of the list. The empty list has no
+/// elements, and objects which are not lists are formatted whole.
 pub fn format_list(vm: &VirtualMachine, ptr: &TypedPointer) -> String {
-    let car = &vm.lists.area[ptr.value].car;
-    let cdr = &vm.lists.area[ptr.value].cdr;
+    if *ptr == ConstSymbol::NIL {
+        return ")".to_owned();
+    }
 
-    let mut s: String = String::new();
+    match make_doc(vm, ptr) {
+        Doc::List { items, tail, .. } => format_list_body(&items, &tail),
+        doc => format_doc(&doc),
+    }
+}
 
-    s.push_str(&format_object(vm, car));
+// Width used by the pretty printer when `*print-right-margin*` is not set
+const DEFAULT_WIDTH: usize = 80;
 
-    if cdr.tag == DataType::Cons {
-        s.push(' ');
-        s.push_str(&format_list(vm, cdr));
-    } else if (cdr.tag == DataType::Atom) && (cdr.value == 0) {
-        // Trick for checking for nil
-        s.push(')');
-    } else {
-        s.push_str(" . ");
-        s.push_str(&format_object(vm, cdr));
-        s.push(')');
+// Special forms and macros whose first arguments stay on the line of the
+// operator when broken, followed by a body indented by two columns
+const SPECIAL_INDENT: &[(&str, usize)] = &[
+    ("fn", 1),
+    ("mac", 1),
+    ("let", 1),
+    ("let*", 1),
+    ("letfn", 1),
+    ("letfn*", 1),
+    ("when", 1),
+    ("unless", 1),
+    ("while", 1),
+    ("until", 1),
+    ("setq", 1),
+    ("def", 1),
+    ("defvar", 1),
+    ("defparameter", 1),
+    ("catch", 1),
+    ("catch-error", 1),
+    ("unwind-protect", 1),
+    ("defn", 2),
+    ("defmac", 2),
+    ("do", 0),
+    ("cond", 0),
+];
+
+/// Truncation limits, taken from `*print-length*` and `*print-level*`.
+struct PrintLimits {
+    length: Option<usize>,
+    level: Option<usize>,
+}
+
+impl PrintLimits {
+    fn from_vm(vm: &VirtualMachine) -> Self {
+        Self {
+            length: print_limit(vm, ConstSymbol::PRINT_LENGTH),
+            level: print_limit(vm, ConstSymbol::PRINT_LEVEL),
+        }
+    }
+}
+
+// Value of a printer variable, if it holds a non-negative integer
+fn print_limit(vm: &VirtualMachine, atom: TypedPointer) -> Option<usize> {
+    let value = &vm.atoms.area.get(atom.value)?.value;
+    if value.tag != DataType::Number {
+        return None;
     }
 
+    match vm.numbers.area[value.value] {
+        Number::Integer(n) if n >= 0 => Some(n as usize),
+        _ => None,
+    }
+}
+
+/// Layout of a printed object.
+/// - `Text`: An object printed on a single piece.
+/// - `List`: A list, with its datum label and the tail of a dotted list,
+///   if any.
+enum Doc {
+    Text(String),
+    List {
+        label: Option<String>,
+        items: Vec<Doc>,
+        tail: Option<Box<Doc>>,
+    },
+}
+
+// Whether `*print-circle*` asks for labels on shared structure too
+fn print_circle(vm: &VirtualMachine) -> bool {
+    vm.atoms
+        .area
+        .get(ConstSymbol::PRINT_CIRCLE.value)
+        .is_some_and(|atom| atom.value.tag == DataType::Atom && atom.value != ConstSymbol::NIL)
+}
+
+/// Finds the conses to print with a datum label: those on a cycle, and
+/// those reached more than once if `shared` is set. The search keeps its
+/// own stack, so that long lists don't overflow the Rust one.
+fn find_labels(vm: &VirtualMachine, root: &TypedPointer, shared: bool) -> HashSet<usize> {
+    // Conses on the current path are `false`, finished ones `true`
+    let mut visited: HashMap<usize, bool> = HashMap::new();
+    let mut labels = HashSet::new();
+    let mut stack = vec![(root.clone(), false)];
+
+    while let Some((ptr, finished)) = stack.pop() {
+        if ptr.tag != DataType::Cons {
+            continue;
+        }
+
+        if finished {
+            visited.insert(ptr.value, true);
+            continue;
+        }
+
+        match visited.get(&ptr.value) {
+            Some(false) => {
+                labels.insert(ptr.value);
+            }
+            Some(true) if shared => {
+                labels.insert(ptr.value);
+            }
+            Some(true) => {}
+            None => {
+                visited.insert(ptr.value, false);
+                let cell = &vm.lists.area[ptr.value];
+                stack.push((ptr.clone(), true));
+                stack.push((cell.cdr.clone(), false));
+                stack.push((cell.car.clone(), false));
+            }
+        }
+    }
+
+    labels
+}
+
+fn make_doc(vm: &VirtualMachine, ptr: &TypedPointer) -> Doc {
+    let mut builder = DocBuilder {
+        vm,
+        limits: PrintLimits::from_vm(vm),
+        labels: find_labels(vm, ptr, print_circle(vm))
+            .into_iter()
+            .map(|cons| (cons, None))
+            .collect(),
+        next_label: 1,
+    };
+    builder.make(ptr, 0)
+}
+
+/// Builds the document of an object, numbering datum labels in the order
+/// they are printed.
+struct DocBuilder<'a> {
+    vm: &'a VirtualMachine,
+    limits: PrintLimits,
+    labels: HashMap<usize, Option<usize>>,
+    next_label: usize,
+}
+
+impl DocBuilder<'_> {
+    fn make(&mut self, ptr: &TypedPointer, depth: usize) -> Doc {
+        if ptr.tag != DataType::Cons {
+            return Doc::Text(format_atom(self.vm, ptr));
+        }
+
+        // Labelled objects are printed once, and referenced afterwards
+        let label = match self.labels.get(&ptr.value) {
+            Some(Some(n)) => return Doc::Text(format!("#{}#", n)),
+            Some(None) => Some(self.next_label),
+            None => None,
+        };
+
+        if self.limits.level.is_some_and(|level| depth >= level) {
+            return Doc::Text("#".to_owned());
+        }
+
+        let label = label.map(|n| {
+            self.labels.insert(ptr.value, Some(n));
+            self.next_label += 1;
+            format!("#{}=", n)
+        });
+
+        let mut items = vec![];
+        let mut tail = None;
+        let mut iter = ptr.clone();
+        loop {
+            if self
+                .limits
+                .length
+                .is_some_and(|length| items.len() >= length)
+            {
+                items.push(Doc::Text("...".to_owned()));
+                return Doc::List { label, items, tail };
+            }
+
+            let cell = &self.vm.lists.area[iter.value];
+            items.push(self.make(&cell.car, depth + 1));
+            iter = cell.cdr.clone();
+
+            // Labelled conses on the rest of the list are printed as a tail
+            if iter.tag != DataType::Cons || self.labels.contains_key(&iter.value) {
+                break;
+            }
+        }
+
+        if iter != ConstSymbol::NIL {
+            tail = Some(Box::new(self.make(&iter, depth + 1)));
+        }
+
+        Doc::List { label, items, tail }
+    }
+}
+
+fn format_doc(doc: &Doc) -> String {
+    match doc {
+        Doc::Text(text) => text.clone(),
+        Doc::List { label, items, tail } => {
+            let mut s = label.clone().unwrap_or_default();
+            s.push('(');
+            s.push_str(&format_list_body(items, tail));
+            s
+        }
+    }
+}
+
+// Items of a list, its tail if dotted, and the closing parenthesis
+fn format_list_body(items: &[Doc], tail: &Option<Box<Doc>>) -> String {
+    let items: Vec<String> = items.iter().map(format_doc).collect();
+    let mut s = items.join(" ");
+    if let Some(tail) = tail {
+        s.push_str(" . ");
+        s.push_str(&format_doc(tail));
+    }
+    s.push(')');
     s
 }
+
+// Column where the next character of the output goes
+fn current_column(out: &str) -> usize {
+    out[out.rfind('\n').map_or(0, |n| n + 1)..].chars().count()
+}
+
+// Lays out a document starting at the given column
+fn layout_doc(doc: &Doc, column: usize, width: usize, out: &mut String) {
+    let flat = format_doc(doc);
+    let (label, items, tail) = match doc {
+        Doc::List { label, items, tail }
+            if column + flat.chars().count() > width && !items.is_empty() =>
+        {
+            (label, items, tail)
+        }
+        _ => {
+            out.push_str(&flat);
+            return;
+        }
+    };
+
+    // The list is laid out after its label
+    let column = match label {
+        Some(label) => {
+            out.push_str(label);
+            column + label.chars().count()
+        }
+        None => column,
+    };
+
+    let newline = |out: &mut String, indent: usize| {
+        out.push('\n');
+        out.push_str(&" ".repeat(indent));
+    };
+
+    // Lists of atoms are filled, fitting as many items as possible per line
+    if tail.is_none() && items.iter().all(|item| matches!(item, Doc::Text(_))) {
+        out.push('(');
+        for (i, item) in items.iter().enumerate() {
+            let text = format_doc(item);
+            if i > 0 {
+                // Room for the item, and the closing parenthesis on the last one
+                let closing = usize::from(i == items.len() - 1);
+                if current_column(out) + 1 + text.chars().count() + closing > width {
+                    newline(out, column + 1);
+                } else {
+                    out.push(' ');
+                }
+            }
+            out.push_str(&text);
+        }
+        out.push(')');
+        return;
+    }
+
+    out.push('(');
+    layout_doc(&items[0], column + 1, width, out);
+
+    // Arguments kept on the first line, and indentation of the others
+    let special = match &items[0] {
+        Doc::Text(name) => SPECIAL_INDENT.iter().find(|(form, _)| form == name),
+        _ => None,
+    };
+    let (inline, indent) = match (&items[0], special) {
+        (_, Some((_, args))) => (*args, column + 2),
+        (Doc::Text(name), None) if name.chars().count() < width / 4 => {
+            (1, column + name.chars().count() + 2)
+        }
+        _ => (0, column + 1),
+    };
+
+    for (i, item) in items.iter().enumerate().skip(1) {
+        if i <= inline {
+            out.push(' ');
+            layout_doc(item, current_column(out), width, out);
+        } else {
+            newline(out, indent);
+            layout_doc(item, indent, width, out);
+        }
+    }
+
+    if let Some(tail) = tail {
+        newline(out, indent);
+        out.push_str(". ");
+        layout_doc(tail, indent + 2, width, out);
+    }
+
+    out.push(')');
+}
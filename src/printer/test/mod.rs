@@ -37,3 +37,134 @@ fn format_atoms() {
 // built-in literals
 // environments
 // cons'es, lists and dotted lists
+
+#[test]
+fn format_truncated() {
+    use crate::vm::types::Number;
+    use crate::vm::ConstSymbol;
+
+    let mut vm = VirtualMachine::new();
+    let obj = util_build_object(&mut vm, "(1 (2 (3 (4))) 5 6)");
+    assert_eq!(super::format_object(&vm, &obj), "(1 (2 (3 (4))) 5 6)");
+
+    let length = vm.make_number(Number::Integer(3)).unwrap();
+    vm.assign_value(ConstSymbol::PRINT_LENGTH, length).unwrap();
+    assert_eq!(super::format_object(&vm, &obj), "(1 (2 (3 (4))) 5 ...)");
+
+    let level = vm.make_number(Number::Integer(2)).unwrap();
+    vm.assign_value(ConstSymbol::PRINT_LEVEL, level).unwrap();
+    assert_eq!(super::format_object(&vm, &obj), "(1 (2 #) 5 ...)");
+
+    vm.assign_value(ConstSymbol::PRINT_LENGTH, ConstSymbol::NIL)
+        .unwrap();
+    vm.assign_value(ConstSymbol::PRINT_LEVEL, ConstSymbol::NIL)
+        .unwrap();
+    assert_eq!(super::format_object(&vm, &obj), "(1 (2 (3 (4))) 5 6)");
+}
+
+#[test]
+fn format_pretty() {
+    let mut vm = VirtualMachine::default();
+
+    // Objects which fit are printed on a single line
+    let obj = util_build_object(&mut vm, "(cons (car x) (cdr y))");
+    assert_eq!(
+        super::format_pretty(&vm, &obj, 80),
+        "(cons (car x) (cdr y))"
+    );
+
+    // Special forms indent their bodies, function calls align arguments
+    let obj = util_build_object(
+        &mut vm,
+        "(setq square (fn (x) (cons (car x) (cons (cdr x) nil))))",
+    );
+    assert_eq!(
+        super::format_pretty(&vm, &obj, 30),
+        "(setq square
+  (fn (x)
+    (cons (car x)
+          (cons (cdr x) nil))))"
+    );
+
+    // Lists of atoms are filled
+    let obj = util_build_object(&mut vm, "(a b c d e f g h i j)");
+    assert_eq!(
+        super::format_pretty(&vm, &obj, 11),
+        "(a b c d e
+ f g h i j)"
+    );
+
+    let obj = util_build_object(&mut vm, "(first-item (second item) . rest)");
+    assert_eq!(
+        super::format_pretty(&vm, &obj, 20),
+        "(first-item
+ (second item)
+ . rest)"
+    );
+}
+
+#[test]
+fn format_circular() {
+    use crate::vm::ConstSymbol;
+
+    let mut vm = VirtualMachine::new();
+
+    // Circular lists are printed with datum labels
+    let obj = util_build_object(&mut vm, "(a b)");
+    let last = vm.get_cdr(&obj).unwrap();
+    vm.set_cdr(&last, obj.clone()).unwrap();
+    assert_eq!(super::format_object(&vm, &obj), "#1=(a b . #1#)");
+
+    let obj = util_build_object(&mut vm, "(a)");
+    vm.set_car(&obj, obj.clone()).unwrap();
+    assert_eq!(super::format_object(&vm, &obj), "#1=(#1#)");
+
+    // Shared structure is labelled only when `*print-circle*` is set
+    let obj = util_build_object(&mut vm, "(#1=(x) #1# (y))");
+    assert_eq!(super::format_object(&vm, &obj), "((x) (x) (y))");
+    vm.assign_value(ConstSymbol::PRINT_CIRCLE, ConstSymbol::T)
+        .unwrap();
+    assert_eq!(super::format_object(&vm, &obj), "(#1=(x) #1# (y))");
+    vm.assign_value(ConstSymbol::PRINT_CIRCLE, ConstSymbol::NIL)
+        .unwrap();
+
+    // Printed labels are read back into the same structure
+    let obj = util_build_object(&mut vm, "#1=(a (b . #1#) . #1#)");
+    let second = vm.get_car(&vm.get_cdr(&obj).unwrap()).unwrap();
+    assert_eq!(vm.get_cdr(&second).unwrap(), obj);
+    let text = super::format_object(&vm, &obj);
+    assert_eq!(text, "#1=(a (b . #1#) . #1#)");
+    let read = util_build_object(&mut vm, &text);
+    assert_eq!(super::format_object(&vm, &read), text);
+}
+
+#[test]
+fn format_list_body() {
+    let mut vm = VirtualMachine::new();
+
+    let obj = util_build_object(&mut vm, "(a (b) . c)");
+    assert_eq!(super::format_list(&vm, &obj), "a (b) . c)");
+
+    // Labels of the list itself are not part of its body
+    let obj = util_build_object(&mut vm, "#1=(a . #1#)");
+    assert_eq!(super::format_list(&vm, &obj), "a . #1#)");
+
+    let obj = util_build_object(&mut vm, "nil");
+    assert_eq!(super::format_list(&vm, &obj), ")");
+}
+
+#[test]
+fn read_label_errors() {
+    use chumsky::prelude::*;
+
+    let mut vm = VirtualMachine::new();
+
+    // Labels which stand for nothing but themselves cannot be read
+    for text in ["#1=#1#", "#1=#2=#1#", "(#1=#1#)"] {
+        let expr = crate::parser::combinators::Combinators::parser()
+            .parse(text)
+            .unwrap();
+        let err = crate::parser::convert::build_ast(&mut vm, expr[0].clone()).unwrap_err();
+        assert_eq!(err.kind_name(), "reader-error", "{}", text);
+    }
+}
@@ -1,5 +1,6 @@
-use super::error::{LispError, LispResult};
+use super::error::{ArgCount, LispError, LispResult};
 use super::types::*;
+use super::ConstSymbol;
 
 impl VirtualMachine {
     pub fn make_atom(&mut self, name: &str) -> LispResult<TypedPointer> {
@@ -18,7 +19,7 @@ impl VirtualMachine {
             };
         }
 
-        if self.atoms.last > ATOM_TABLE_SIZE {
+        if self.atoms.last >= ATOM_TABLE_SIZE {
             return Err(LispError::atom_table_allocation());
         }
 
@@ -33,8 +34,9 @@ impl VirtualMachine {
             .map_err(|_| LispError::atom_table_allocation())?;
         atom.name = String::from(name);
         atom.value = TypedPointer::new(DataType::Undefined, 0);
-        // atom.bindlist = 0;
-        // atom.plist = 0;
+        atom.plist = ConstSymbol::NIL;
+        atom.bindlist = ConstSymbol::NIL;
+        atom.special = false;
 
         let _ = self.atom_index.insert(String::from(name), ptr);
 
@@ -50,9 +52,19 @@ impl VirtualMachine {
         Ok(TypedPointer::new(DataType::Number, ptr))
     }
 
+    pub fn make_string(&mut self, value: &str) -> LispResult<TypedPointer> {
+        let ptr = self.strings.last;
+        if ptr >= STRING_TABLE_SIZE {
+            return Err(LispError::string_table_allocation());
+        }
+        self.strings.last += 1;
+        self.strings.area[ptr] = String::from(value);
+        Ok(TypedPointer::new(DataType::String, ptr))
+    }
+
     pub fn assign_value(&mut self, atom: TypedPointer, value: TypedPointer) -> LispResult<()> {
         if atom.tag != DataType::Atom {
-            return Err(LispError::internal("attempted to assign value to non-atom"));
+            return Err(self.type_error("symbol", &atom));
         }
 
         if atom.value >= self.atoms.last {
@@ -84,7 +96,7 @@ impl VirtualMachine {
 
     pub fn lookup_atom_value(&self, atom: TypedPointer) -> LispResult<TypedPointer> {
         if atom.tag != DataType::Atom {
-            return Err(LispError::internal("attempted to lookup value of non-atom"));
+            return Err(self.type_error("symbol", &atom));
         }
 
         if atom.value >= self.atoms.last {
@@ -99,3 +111,157 @@ impl VirtualMachine {
         Ok(atom.value.clone())
     }
 }
+
+impl VirtualMachine {
+    /// Names of the symbols starting with `prefix` which are bound globally
+    /// or on E0, in alphabetical order.
+    pub fn complete_symbol(&self, prefix: &str) -> Vec<String> {
+        use radix_trie::TrieCommon;
+
+        let candidates: Vec<(&String, &usize)> = if prefix.is_empty() {
+            self.atom_index.iter().collect()
+        } else {
+            match self.atom_index.get_raw_descendant(prefix) {
+                Some(subtrie) => subtrie.iter().collect(),
+                None => vec![],
+            }
+        };
+
+        let mut names: Vec<String> = candidates
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(prefix))
+            .filter(|(_, ptr)| self.toplevel_value(**ptr).is_some())
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Describes the arguments of the function bound to a symbol: the
+    /// lambda list of a closure or macro, or the arity of a built-in
+    /// function.
+    pub fn function_signature(&self, name: &str) -> Option<String> {
+        let fun = self.toplevel_value(*self.atom_index.get(name)?)?;
+
+        match fun.tag {
+            DataType::Function | DataType::Macro => {
+                let (lambda_list, _, _) = self.closure_parts(&fun).ok()?;
+                Some(crate::printer::format_lambda_list(self, &lambda_list))
+            }
+            DataType::BuiltInFunction => match super::primitive_eval::builtin_arity(&fun)? {
+                ArgCount::AtLeast(0) => Some("any number of arguments".to_owned()),
+                ArgCount::Exactly(1) => Some("1 argument".to_owned()),
+                arity => Some(format!("{} arguments", arity)),
+            },
+            _ => None,
+        }
+    }
+
+    // Value of a symbol on the global scope or on E0, if bound.
+    fn toplevel_value(&self, atom: UntypedPointer) -> Option<TypedPointer> {
+        let value = self.atoms.area.get(atom)?.value.clone();
+        if value.tag != DataType::Undefined {
+            return Some(value);
+        }
+
+        let atom = TypedPointer::new(DataType::Atom, atom);
+        self.env_lookup(ConstSymbol::E0, atom).ok().flatten()
+    }
+
+    fn get_atom(&self, atom: &TypedPointer) -> LispResult<&Atom> {
+        if atom.tag != DataType::Atom {
+            return Err(self.type_error("symbol", atom));
+        }
+
+        if atom.value >= self.atoms.last {
+            return Err(LispError::internal(
+                "attempted to access property list of unallocated atom",
+            ));
+        }
+
+        Ok(&self.atoms.area[atom.value])
+    }
+
+    pub fn symbol_plist(&self, atom: TypedPointer) -> LispResult<TypedPointer> {
+        Ok(self.get_atom(&atom)?.plist.clone())
+    }
+
+    pub fn get_property(
+        &self,
+        atom: TypedPointer,
+        property: TypedPointer,
+    ) -> LispResult<TypedPointer> {
+        let mut iter = self.get_atom(&atom)?.plist.clone();
+
+        while iter != ConstSymbol::NIL {
+            let rest = self.get_cdr(&iter)?;
+            if self.values_eq(&self.get_car(&iter)?, &property)? {
+                return self.get_car(&rest);
+            }
+            iter = self.get_cdr(&rest)?;
+        }
+
+        Ok(ConstSymbol::NIL)
+    }
+
+    pub fn put_property(
+        &mut self,
+        atom: TypedPointer,
+        property: TypedPointer,
+        value: TypedPointer,
+    ) -> LispResult<()> {
+        let plist = self.get_atom(&atom)?.plist.clone();
+
+        // Replace value if property is already there
+        let mut iter = plist.clone();
+        while iter != ConstSymbol::NIL {
+            let rest = self.get_cdr(&iter)?;
+            if self.values_eq(&self.get_car(&iter)?, &property)? {
+                return self.set_car(&rest, value);
+            }
+            iter = self.get_cdr(&rest)?;
+        }
+
+        // Otherwise push (property value) to the front of the plist
+        let value_cell = self.make_cons()?;
+        self.set_car(&value_cell, value)?;
+        self.set_cdr(&value_cell, plist)?;
+
+        let property_cell = self.make_cons()?;
+        self.set_car(&property_cell, property)?;
+        self.set_cdr(&property_cell, value_cell)?;
+
+        self.atoms.area[atom.value].plist = property_cell;
+        Ok(())
+    }
+
+    /// Removes a property from the property list of an atom. Returns whether
+    /// the property was found.
+    pub fn remove_property(
+        &mut self,
+        atom: TypedPointer,
+        property: TypedPointer,
+    ) -> LispResult<bool> {
+        let mut prev = ConstSymbol::NIL;
+        let mut iter = self.get_atom(&atom)?.plist.clone();
+
+        while iter != ConstSymbol::NIL {
+            let rest = self.get_cdr(&iter)?;
+            let next = self.get_cdr(&rest)?;
+
+            if self.values_eq(&self.get_car(&iter)?, &property)? {
+                if prev == ConstSymbol::NIL {
+                    self.atoms.area[atom.value].plist = next;
+                } else {
+                    self.set_cdr(&prev, next)?;
+                }
+                return Ok(true);
+            }
+
+            prev = rest;
+            iter = next;
+        }
+
+        Ok(false)
+    }
+}
@@ -0,0 +1,90 @@
+use super::error::LispResult;
+use super::{types::*, ConstSymbol};
+
+impl VirtualMachine {
+    /// Records a frame for an application starting at the current stack
+    /// depth. Deeper frames belong to applications which already returned.
+    /// A frame on the same depth is a function whose body is making a tail
+    /// call: it is kept so that it shows on backtraces, but only the latest
+    /// such frame is kept per depth, so that tail calls run in constant space.
+    pub fn frame_push(&mut self, form: TypedPointer) {
+        let depth = self.stack.last;
+        self.frame_prune(depth + 1);
+
+        let n = self.frames.len();
+        if self.frames.last().is_some_and(|f| f.depth == depth) {
+            let caller = self.frames[n - 1].fun.tag == DataType::Function;
+            let tail_called = (n >= 2) && (self.frames[n - 2].depth == depth);
+            if !caller {
+                self.frames.pop();
+            } else if tail_called {
+                // Only the latest function making a tail call is kept
+                self.frames.remove(n - 2);
+            }
+        }
+
+        self.frames.push(Frame {
+            form,
+            fun: TypedPointer::default(),
+            depth,
+        });
+    }
+
+    /// Records the function being applied on the current application frame.
+    /// When applying, only the continuation of the application is left on
+    /// the stack above the frame's depth.
+    pub fn frame_set_function(&mut self, fun: TypedPointer) {
+        let depth = self.stack.last.saturating_sub(1);
+        if let Some(frame) = self.frames.last_mut() {
+            if frame.depth == depth {
+                frame.fun = fun;
+            }
+        }
+    }
+
+    /// Discards frames starting at `depth` or deeper.
+    pub fn frame_prune(&mut self, depth: usize) {
+        while self.frames.last().is_some_and(|f| f.depth >= depth) {
+            self.frames.pop();
+        }
+    }
+
+    /// Frames of the current evaluation, innermost first.
+    pub fn backtrace(&self) -> Vec<Frame> {
+        self.frames
+            .iter()
+            .rev()
+            .filter(|f| f.depth >= self.stack.base)
+            .cloned()
+            .collect()
+    }
+
+    /// Name of the function applied on a frame: the operator symbol if
+    /// there is one, otherwise the function object itself.
+    pub fn frame_name(&self, frame: &Frame) -> LispResult<TypedPointer> {
+        let operator = if frame.form.tag == DataType::Cons {
+            self.get_car(&frame.form)?
+        } else {
+            TypedPointer::default()
+        };
+
+        Ok(if operator.tag == DataType::Atom {
+            operator
+        } else if frame.fun.tag != DataType::Undefined {
+            frame.fun.clone()
+        } else {
+            ConstSymbol::NIL
+        })
+    }
+
+    /// Lisp-visible backtrace: a list of `(name form)` entries, innermost
+    /// first.
+    pub fn backtrace_list(&mut self) -> LispResult<TypedPointer> {
+        let mut entries = vec![];
+        for frame in self.backtrace() {
+            let name = self.frame_name(&frame)?;
+            entries.push(self.make_list(&[name, frame.form])?);
+        }
+        self.make_list(&entries)
+    }
+}
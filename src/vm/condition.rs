@@ -0,0 +1,62 @@
+use super::error::{LispError, LispResult};
+use super::{types::*, ConstSymbol};
+
+// Error objects are literals stored on the list area, with the layout
+// (error <kind> <message> <irritants>)
+impl VirtualMachine {
+    pub fn make_error_object(
+        &mut self,
+        kind: TypedPointer,
+        message: TypedPointer,
+        irritants: TypedPointer,
+    ) -> LispResult<TypedPointer> {
+        let list = self.make_list(&[ConstSymbol::ERROR, kind, message, irritants])?;
+        Ok(TypedPointer::new(DataType::Literal, list.value))
+    }
+
+    /// Converts a Rust-side error into a Lisp-visible error object.
+    pub fn lisp_error_object(&mut self, error: &LispError) -> LispResult<TypedPointer> {
+        if let Some(object) = error.object() {
+            return Ok(object);
+        }
+
+        let kind = self.make_atom(error.kind_name())?;
+        let message = self.make_string(&error.message())?;
+        self.make_error_object(kind, message, ConstSymbol::NIL)
+    }
+
+    pub fn is_error_object(&self, ptr: &TypedPointer) -> bool {
+        (ptr.tag == DataType::Literal) && (self.lists.area[ptr.value].car == ConstSymbol::ERROR)
+    }
+
+    /// Returns a tuple (kind, message, irritants)
+    pub fn error_object_parts(
+        &self,
+        ptr: &TypedPointer,
+    ) -> LispResult<(TypedPointer, TypedPointer, TypedPointer)> {
+        if !self.is_error_object(ptr) {
+            return Err(self.type_error("error", ptr));
+        }
+
+        let list = TypedPointer::new(DataType::Cons, ptr.value);
+        let kind = self.get_cadr(&list)?;
+        let message = self.get_caddr(&list)?;
+        let irritants = self.get_car(&self.get_cdr(&self.get_cdr(&self.get_cdr(&list)?)?)?)?;
+
+        Ok((kind, message, irritants))
+    }
+
+    /// Type error for a value, which is printed on the error message.
+    pub fn type_error(&self, expected: &'static str, value: &TypedPointer) -> LispError {
+        LispError::type_error(
+            expected,
+            value.tag.type_name(),
+            crate::printer::format_object(self, value),
+        )
+    }
+
+    /// Error for applying a value which is not a function.
+    pub fn not_a_function(&self, value: &TypedPointer) -> LispError {
+        LispError::not_a_function(crate::printer::format_object(self, value))
+    }
+}
@@ -93,6 +93,60 @@ impl ConstSymbol {
         tag: DataType::Atom,
         value: 21,
     };
+    pub const DEFVAR: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 22,
+    };
+    pub const DEFPARAMETER: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 23,
+    };
+    pub const CATCH_ERROR: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 24,
+    };
+    pub const CATCH: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 25,
+    };
+    pub const UNWIND_PROTECT: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 26,
+    };
+    pub const IF: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 27,
+    };
+
+    // Reserved symbols, which are not special forms
+    pub const THROW: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 28,
+    };
+    pub const CONTINUATION: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 29,
+    };
+    pub const LOAD_FILES: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 30,
+    };
+    pub const PRINT_LENGTH: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 31,
+    };
+    pub const PRINT_LEVEL: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 32,
+    };
+    pub const PRINT_RIGHT_MARGIN: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 33,
+    };
+    pub const PRINT_CIRCLE: TypedPointer = TypedPointer {
+        tag: DataType::Atom,
+        value: 34,
+    };
 }
 
 // Built-in literals, used on evaluator mostly
@@ -117,6 +171,74 @@ impl ConstSymbol {
         tag: DataType::BuiltInLiteral,
         value: 4,
     };
+    pub const EVAL_DEFINE: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 5,
+    };
+    pub const UNBIND_DYNAMIC: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 6,
+    };
+    pub const EVAL_SEQUENCE_CONTINUE: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 7,
+    };
+    pub const POP_ERROR_HANDLER: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 9,
+    };
+    pub const APPLY_ERROR_HANDLER: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 10,
+    };
+    pub const CATCH_TAG_EVALUATED: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 12,
+    };
+    pub const POP_CATCH: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 13,
+    };
+    pub const UNWIND_PROTECT_CLEANUP: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 14,
+    };
+    pub const UNWIND_PROTECT_RETURN: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 15,
+    };
+    pub const RESUME_UNWIND: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 16,
+    };
+    pub const IF_DECIDE: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 19,
+    };
+    pub const EVAL_EXPANSION: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 20,
+    };
+}
+
+// Stack markers, used to delimit special frames on the stack
+impl ConstSymbol {
+    pub const DYNAMIC_FRAME: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 8,
+    };
+    pub const ERROR_HANDLER_FRAME: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 11,
+    };
+    pub const CATCH_FRAME: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 17,
+    };
+    pub const UNWIND_PROTECT_FRAME: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInLiteral,
+        value: 18,
+    };
 }
 
 // Default environment
@@ -158,4 +280,111 @@ impl ConstSymbol {
         tag: DataType::BuiltInFunction,
         value: 5,
     };
+
+    pub const BIN_GET: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 6,
+    };
+
+    pub const BIN_PUT: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 7,
+    };
+
+    pub const BIN_REMPROP: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 8,
+    };
+
+    pub const BIN_SYMBOL_PLIST: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 9,
+    };
+
+    pub const BIN_PRINT_ENV: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 10,
+    };
+
+    pub const BIN_ERR: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 11,
+    };
+
+    pub const BIN_ERRORP: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 12,
+    };
+
+    pub const BIN_ERROR_KIND: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 13,
+    };
+
+    pub const BIN_ERROR_MESSAGE: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 14,
+    };
+
+    pub const BIN_ERROR_IRRITANTS: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 15,
+    };
+
+    pub const BIN_THROW: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 16,
+    };
+
+    pub const BIN_CALL_CC: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 17,
+    };
+
+    pub const BIN_APPLY: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 18,
+    };
+
+    pub const BIN_FUNCALL: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 19,
+    };
+
+    pub const BIN_THE_ENVIRONMENT: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 20,
+    };
+
+    pub const BIN_BACKTRACE: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 21,
+    };
+
+    pub const BIN_LOAD: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 22,
+    };
+
+    pub const BIN_PPRINT: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 23,
+    };
+
+    pub const BIN_DESCRIBE: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 24,
+    };
+    pub const BIN_APPEND: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 25,
+    };
+    pub const BIN_EQUAL: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 26,
+    };
+    pub const BIN_LENGTH: TypedPointer = TypedPointer {
+        tag: DataType::BuiltInFunction,
+        value: 27,
+    };
 }
@@ -0,0 +1,96 @@
+use super::error::{LispError, LispResult};
+use super::{types::*, ConstSymbol};
+use std::collections::HashMap;
+
+// Continuations are literals stored on the list area, with the layout
+// (continuation <cont> <stack segment> <dynamic values>)
+//
+// The stack segment holds the stack of the current evaluation, bottom first.
+// Dynamic values hold, for each dynamic frame on the segment, the values its
+// special variables were bound to, so that these bindings can be
+// re-established when the continuation is reinstated. Unwind-protect cleanup
+// forms are not run when jumping in or out of a continuation.
+impl VirtualMachine {
+    /// Captures the stack of the current evaluation, up to (and excluding)
+    /// position `top`, and the given `cont` register as a continuation.
+    pub fn capture_continuation(
+        &mut self,
+        cont: TypedPointer,
+        top: usize,
+    ) -> LispResult<TypedPointer> {
+        let base = self.stack.base;
+        let segment = self.stack.area[base..top].to_vec();
+
+        // Find values bound by dynamic frames, from innermost to outermost.
+        // The innermost binding of a symbol holds its current value, and
+        // outer bindings hold the values saved on its bindlist.
+        let mut depths: HashMap<TypedPointer, usize> = HashMap::new();
+        let mut frame_values = vec![];
+        for i in (1..segment.len()).rev() {
+            if segment[i] != ConstSymbol::DYNAMIC_FRAME {
+                continue;
+            }
+
+            let mut values = vec![];
+            for symbol in self.list_to_vec(segment[i - 1].clone())? {
+                let depth = depths.entry(symbol.clone()).or_insert(0);
+                let value = if *depth == 0 {
+                    self.lookup_atom_value(symbol.clone())?
+                } else {
+                    let bindlist = self.atoms.area[symbol.value].bindlist.clone();
+                    self.list_to_vec(bindlist)?
+                        .get(*depth - 1)
+                        .cloned()
+                        .unwrap_or_default()
+                };
+                *depth += 1;
+                values.push(value);
+            }
+            frame_values.push(self.make_list(&values)?);
+        }
+        frame_values.reverse();
+
+        let segment = self.make_list(&segment)?;
+        let frame_values = self.make_list(&frame_values)?;
+        let list = self.make_list(&[ConstSymbol::CONTINUATION, cont, segment, frame_values])?;
+
+        Ok(TypedPointer::new(DataType::Literal, list.value))
+    }
+
+    pub fn is_continuation(&self, ptr: &TypedPointer) -> bool {
+        (ptr.tag == DataType::Literal)
+            && (self.lists.area[ptr.value].car == ConstSymbol::CONTINUATION)
+    }
+
+    /// Replaces the stack of the current evaluation by the stack segment of
+    /// a continuation, re-establishing its dynamic bindings. Returns the
+    /// `cont` register of the continuation.
+    pub fn reinstate_continuation(&mut self, ptr: &TypedPointer) -> LispResult<TypedPointer> {
+        if !self.is_continuation(ptr) {
+            return Err(LispError::internal(
+                "attempted to reinstate non-continuation",
+            ));
+        }
+
+        let list = self.list_to_vec(TypedPointer::new(DataType::Cons, ptr.value))?;
+        let cont = list[1].clone();
+        let segment = self.list_to_vec(list[2].clone())?;
+        let mut frame_values = self.list_to_vec(list[3].clone())?.into_iter();
+
+        self.stack_unwind_to(self.stack.base)?;
+
+        for item in segment {
+            self.stack_push(item.clone())?;
+
+            if item == ConstSymbol::DYNAMIC_FRAME {
+                let symbols = self.list_to_vec(self.stack.area[self.stack.last - 2].clone())?;
+                let values = self.list_to_vec(frame_values.next().unwrap_or_default())?;
+                for (symbol, value) in symbols.into_iter().zip(values) {
+                    self.dynamic_bind(symbol, value)?;
+                }
+            }
+        }
+
+        Ok(cont)
+    }
+}
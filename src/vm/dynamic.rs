@@ -0,0 +1,98 @@
+use super::error::{LispError, LispResult};
+use super::{types::*, ConstSymbol};
+
+impl VirtualMachine {
+    fn get_special_atom_mut(&mut self, atom: &TypedPointer) -> LispResult<&mut Atom> {
+        if atom.tag != DataType::Atom {
+            return Err(LispError::internal(
+                "attempted to dynamically bind a non-atom",
+            ));
+        }
+
+        if atom.value >= self.atoms.last {
+            return Err(LispError::internal(
+                "attempted to dynamically bind an unallocated atom",
+            ));
+        }
+
+        Ok(&mut self.atoms.area[atom.value])
+    }
+
+    pub fn declare_special(&mut self, atom: TypedPointer) -> LispResult<()> {
+        self.get_special_atom_mut(&atom)?.special = true;
+        Ok(())
+    }
+
+    pub fn is_special(&self, atom: &TypedPointer) -> bool {
+        (atom.tag == DataType::Atom)
+            && self
+                .atoms
+                .area
+                .get(atom.value)
+                .map(|a| a.special)
+                .unwrap_or(false)
+    }
+
+    /// Shallow binding: saves the current value of a special variable on its
+    /// bindlist, then stores the new value directly on the atom.
+    pub fn dynamic_bind(&mut self, atom: TypedPointer, value: TypedPointer) -> LispResult<()> {
+        let (old_value, bindlist) = {
+            let atom = self.get_special_atom_mut(&atom)?;
+            (atom.value.clone(), atom.bindlist.clone())
+        };
+
+        let cons = self.make_cons()?;
+        self.set_car(&cons, old_value)?;
+        self.set_cdr(&cons, bindlist)?;
+
+        let atom = self.get_special_atom_mut(&atom)?;
+        atom.bindlist = cons;
+        atom.value = value;
+        Ok(())
+    }
+
+    /// Restores the value of a special variable saved by its latest dynamic
+    /// binding.
+    pub fn dynamic_unbind(&mut self, atom: TypedPointer) -> LispResult<()> {
+        let bindlist = self.get_special_atom_mut(&atom)?.bindlist.clone();
+        if bindlist == ConstSymbol::NIL {
+            return Err(LispError::internal(
+                "attempted to unbind a special variable with no dynamic bindings",
+            ));
+        }
+
+        let old_value = self.get_car(&bindlist)?;
+        let rest = self.get_cdr(&bindlist)?;
+
+        let atom = self.get_special_atom_mut(&atom)?;
+        atom.value = old_value;
+        atom.bindlist = rest;
+        Ok(())
+    }
+
+    /// Unbinds every special variable on a list of symbols.
+    pub fn dynamic_unbind_list(&mut self, symbols: TypedPointer) -> LispResult<()> {
+        let mut iter = symbols;
+        while iter != ConstSymbol::NIL {
+            self.dynamic_unbind(self.get_car(&iter)?)?;
+            iter = self.get_cdr(&iter)?;
+        }
+        Ok(())
+    }
+
+    /// Pops the stack until it has `depth` elements, restoring the dynamic
+    /// bindings of every dynamic frame found along the way. Returns the
+    /// number of popped elements.
+    pub fn stack_unwind_to(&mut self, depth: usize) -> LispResult<usize> {
+        let mut popped = 0;
+        while self.stack.last > depth {
+            if self.stack_pop()? == ConstSymbol::DYNAMIC_FRAME {
+                let symbols = self.stack_pop()?;
+                self.dynamic_unbind_list(symbols)?;
+                popped += 1;
+            }
+            popped += 1;
+        }
+        Ok(popped)
+    }
+}
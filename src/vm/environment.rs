@@ -9,7 +9,7 @@ impl VirtualMachine {
             ));
         }
 
-        if self.environments.last > ENV_TABLE_SIZE {
+        if self.environments.last >= ENV_TABLE_SIZE {
             return Err(LispError::environment_table_allocation());
         }
 
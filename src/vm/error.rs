@@ -1,68 +1,327 @@
+use super::types::{Frame, TypedPointer};
+use chumsky::prelude::Simple;
 use std::{error::Error, fmt};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 enum LispErrorKind {
     StackOverflow,
     StackUnderflow,
     AtomTableAllocation,
     NumberTableAllocation,
+    StringTableAllocation,
     ListAreaAllocation,
     EnvironmentTableAllocation,
     Internal(&'static str),
-    Arity(String),
+    UnboundVariable(String),
+    TypeError {
+        expected: &'static str,
+        actual: &'static str,
+        value: String,
+    },
+    NotAFunction(String),
+    Load(String),
+    Syntax {
+        file: usize,
+        name: String,
+        errors: Vec<Simple<char>>,
+    },
+    Read(String),
+    WrongArgCount {
+        function: String,
+        expected: ArgCount,
+        received: usize,
+    },
+    Signal(TypedPointer, String),
+    Throw(TypedPointer, TypedPointer, String),
+    Control(String),
 }
 
-#[derive(Debug)]
+/// Number of arguments accepted by a function.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ArgCount {
+    Exactly(usize),
+    AtLeast(usize),
+    Between(usize, usize),
+}
+
+impl fmt::Display for ArgCount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgCount::Exactly(n) => write!(f, "{}", n),
+            ArgCount::AtLeast(n) => write!(f, "at least {}", n),
+            ArgCount::Between(min, max) => write!(f, "{} to {}", min, max),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct LispError {
     kind: LispErrorKind,
+    discarded: usize,
+    backtrace: Vec<Frame>,
 }
 
 impl LispError {
-    pub fn stack_overflow() -> Self {
+    fn new(kind: LispErrorKind) -> Self {
         Self {
-            kind: LispErrorKind::StackOverflow,
+            kind,
+            discarded: 0,
+            backtrace: vec![],
         }
     }
 
+    pub fn stack_overflow() -> Self {
+        Self::new(LispErrorKind::StackOverflow)
+    }
+
     pub fn stack_underflow() -> Self {
-        Self {
-            kind: LispErrorKind::StackUnderflow,
-        }
+        Self::new(LispErrorKind::StackUnderflow)
     }
 
     pub fn atom_table_allocation() -> Self {
-        Self {
-            kind: LispErrorKind::AtomTableAllocation,
-        }
+        Self::new(LispErrorKind::AtomTableAllocation)
     }
 
     pub fn number_table_allocation() -> Self {
-        Self {
-            kind: LispErrorKind::NumberTableAllocation,
-        }
+        Self::new(LispErrorKind::NumberTableAllocation)
+    }
+
+    pub fn string_table_allocation() -> Self {
+        Self::new(LispErrorKind::StringTableAllocation)
     }
 
     pub fn list_area_allocation() -> Self {
-        Self {
-            kind: LispErrorKind::ListAreaAllocation,
-        }
+        Self::new(LispErrorKind::ListAreaAllocation)
     }
 
     pub fn environment_table_allocation() -> Self {
-        Self {
-            kind: LispErrorKind::EnvironmentTableAllocation,
+        Self::new(LispErrorKind::EnvironmentTableAllocation)
+    }
+
+    /// Reference to a symbol with no value.
+    pub fn unbound_variable(name: String) -> Self {
+        Self::new(LispErrorKind::UnboundVariable(name))
+    }
+
+    /// Value of the wrong type. `value` is the printed offending value.
+    pub fn type_error(expected: &'static str, actual: &'static str, value: String) -> Self {
+        Self::new(LispErrorKind::TypeError {
+            expected,
+            actual,
+            value,
+        })
+    }
+
+    /// Application of something that is not a function. `value` is the
+    /// printed offending value.
+    pub fn not_a_function(value: String) -> Self {
+        Self::new(LispErrorKind::NotAFunction(value))
+    }
+
+    /// File could not be loaded.
+    pub fn load(reason: String) -> Self {
+        Self::new(LispErrorKind::Load(reason))
+    }
+
+    /// Loaded file could not be parsed. `file` is the index of its text on
+    /// the source table.
+    pub fn syntax(file: usize, name: String, errors: Vec<Simple<char>>) -> Self {
+        Self::new(LispErrorKind::Syntax { file, name, errors })
+    }
+
+    /// Expression could not be read, even though it parsed.
+    pub fn read(reason: String) -> Self {
+        Self::new(LispErrorKind::Read(reason))
+    }
+
+    pub fn wrong_arg_count(function: String, expected: ArgCount, received: usize) -> Self {
+        Self::new(LispErrorKind::WrongArgCount {
+            function,
+            expected,
+            received,
+        })
+    }
+
+    pub fn internal(reason: &'static str) -> Self {
+        Self::new(LispErrorKind::Internal(reason))
+    }
+
+    /// Error signalled from Lisp code. Holds the Lisp-visible error object
+    /// and its formatted message.
+    pub fn signal(object: TypedPointer, message: String) -> Self {
+        Self::new(LispErrorKind::Signal(object, message))
+    }
+
+    /// Non-local exit to the catch point for a tag. Holds the tag, the thrown
+    /// value and the printed tag, used when no catch point is found.
+    pub fn throw(tag: TypedPointer, value: TypedPointer, tag_name: String) -> Self {
+        Self::new(LispErrorKind::Throw(tag, value, tag_name))
+    }
+
+    /// Error for a throw which has no catch point for its tag. Other errors
+    /// are left as they are.
+    pub(crate) fn uncaught_throw(self) -> Self {
+        match &self.kind {
+            LispErrorKind::Throw(_, _, tag) => {
+                Self::new(LispErrorKind::Control(format!("no catch for tag {}", tag)))
+            }
+            _ => self,
         }
     }
 
-    pub fn arity(fn_name: String) -> Self {
-        Self {
-            kind: LispErrorKind::Arity(fn_name),
+    /// Tag and value of a throw, if this is a non-local exit to a catch point.
+    pub fn thrown(&self) -> Option<(TypedPointer, TypedPointer)> {
+        match &self.kind {
+            LispErrorKind::Throw(tag, value, _) => Some((tag.clone(), value.clone())),
+            _ => None,
         }
     }
 
-    pub fn internal(reason: &'static str) -> Self {
-        Self {
-            kind: LispErrorKind::Internal(reason),
+    /// Name of the symbol, if this is a reference to an unbound variable.
+    pub fn unbound_symbol(&self) -> Option<&str> {
+        match &self.kind {
+            LispErrorKind::UnboundVariable(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Expected and actual type names, if this is a type error.
+    pub fn type_mismatch(&self) -> Option<(&'static str, &'static str)> {
+        match &self.kind {
+            LispErrorKind::TypeError {
+                expected, actual, ..
+            } => Some((expected, actual)),
+            _ => None,
+        }
+    }
+
+    /// Printed value which caused a type error or which is not a function.
+    pub fn offending_value(&self) -> Option<&str> {
+        match &self.kind {
+            LispErrorKind::TypeError { value, .. } | LispErrorKind::NotAFunction(value) => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn is_not_a_function(&self) -> bool {
+        matches!(self.kind, LispErrorKind::NotAFunction(_))
+    }
+
+    /// Function name, expected and received argument count, if this is an
+    /// application with the wrong number of arguments.
+    pub fn arg_count(&self) -> Option<(&str, ArgCount, usize)> {
+        match &self.kind {
+            LispErrorKind::WrongArgCount {
+                function,
+                expected,
+                received,
+            } => Some((function, *expected, *received)),
+            _ => None,
+        }
+    }
+
+    /// Lisp-visible error object, if this error was signalled by Lisp code.
+    pub fn object(&self) -> Option<TypedPointer> {
+        match &self.kind {
+            LispErrorKind::Signal(object, _) => Some(object.clone()),
+            _ => None,
+        }
+    }
+
+    /// Name of the symbol used as error kind on Lisp-visible error objects.
+    pub fn kind_name(&self) -> &'static str {
+        match &self.kind {
+            LispErrorKind::StackOverflow => "stack-overflow",
+            LispErrorKind::StackUnderflow => "stack-underflow",
+            LispErrorKind::AtomTableAllocation
+            | LispErrorKind::NumberTableAllocation
+            | LispErrorKind::StringTableAllocation
+            | LispErrorKind::ListAreaAllocation
+            | LispErrorKind::EnvironmentTableAllocation => "allocation-error",
+            LispErrorKind::Internal(_) => "internal-error",
+            LispErrorKind::UnboundVariable(_) => "unbound-variable",
+            LispErrorKind::TypeError { .. } => "type-error",
+            LispErrorKind::NotAFunction(_) => "not-a-function",
+            LispErrorKind::Load(_) | LispErrorKind::Syntax { .. } => "load-error",
+            LispErrorKind::Read(_) => "reader-error",
+            LispErrorKind::WrongArgCount { .. } => "arity-error",
+            LispErrorKind::Signal(_, _) => "simple-error",
+            LispErrorKind::Throw(_, _, _) | LispErrorKind::Control(_) => "control-error",
+        }
+    }
+
+    /// Source file index and parse errors of a file which failed to load.
+    pub fn syntax_errors(&self) -> Option<(usize, &[Simple<char>])> {
+        match &self.kind {
+            LispErrorKind::Syntax { file, errors, .. } => Some((*file, errors)),
+            _ => None,
+        }
+    }
+
+    /// Number of stack entries discarded when the failed evaluation was
+    /// abandoned.
+    pub fn discarded(&self) -> usize {
+        self.discarded
+    }
+
+    pub(crate) fn with_discarded(mut self, discarded: usize) -> Self {
+        self.discarded = discarded;
+        self
+    }
+
+    /// Application frames active when the error was raised, innermost
+    /// first.
+    pub fn backtrace(&self) -> &[Frame] {
+        &self.backtrace
+    }
+
+    pub(crate) fn with_backtrace(mut self, backtrace: Vec<Frame>) -> Self {
+        self.backtrace = backtrace;
+        self
+    }
+
+    pub fn message(&self) -> String {
+        match &self.kind {
+            LispErrorKind::StackOverflow => "stack overflow".to_owned(),
+            LispErrorKind::StackUnderflow => "stack underflow".to_owned(),
+            LispErrorKind::AtomTableAllocation => "atom table allocation error".to_owned(),
+            LispErrorKind::NumberTableAllocation => "number table allocation error".to_owned(),
+            LispErrorKind::StringTableAllocation => "string table allocation error".to_owned(),
+            LispErrorKind::ListAreaAllocation => "list area allocation error".to_owned(),
+            LispErrorKind::EnvironmentTableAllocation => {
+                "environment area allocation error".to_owned()
+            }
+            LispErrorKind::UnboundVariable(name) => format!("unbound variable: {}", name),
+            LispErrorKind::TypeError {
+                expected,
+                actual,
+                value,
+            } => format!(
+                "type error: expected {}, got {} {}",
+                expected, actual, value
+            ),
+            LispErrorKind::NotAFunction(value) => format!("not a function: {}", value),
+            LispErrorKind::Load(reason) => format!("load error: {}", reason),
+            LispErrorKind::Syntax { name, errors, .. } => format!(
+                "load error: syntax error in {} at {}",
+                name,
+                errors.first().map(|e| e.span().start).unwrap_or_default()
+            ),
+            LispErrorKind::Read(reason) => format!("reader error: {}", reason),
+            LispErrorKind::WrongArgCount {
+                function,
+                expected,
+                received,
+            } => format!(
+                "wrong number of arguments to {}: expected {}, got {}",
+                function, expected, received
+            ),
+            LispErrorKind::Internal(cause) => format!("internal error: {}", cause),
+            LispErrorKind::Signal(_, message) => message.clone(),
+            LispErrorKind::Throw(_, _, tag) => format!("no catch for tag {}", tag),
+            LispErrorKind::Control(message) => message.clone(),
         }
     }
 }
@@ -74,18 +333,7 @@ impl fmt::Display for LispError {
         write!(
             f,
             "Error with Majestic Lisp environment: {}",
-            match &self.kind {
-                LispErrorKind::StackOverflow => "stack overflow".to_owned(),
-                LispErrorKind::StackUnderflow => "stack underflow".to_owned(),
-                LispErrorKind::AtomTableAllocation => "atom table allocation error".to_owned(),
-                LispErrorKind::NumberTableAllocation => "number table allocation error".to_owned(),
-                LispErrorKind::ListAreaAllocation => "list area allocation error".to_owned(),
-                LispErrorKind::EnvironmentTableAllocation =>
-                    "environment area allocation error".to_owned(),
-                LispErrorKind::Arity(name) =>
-                    format!("arity error while applying function {}", name),
-                LispErrorKind::Internal(cause) => format!("internal error: {}", cause),
-            }
+            self.message()
         )
     }
 }
@@ -1,9 +1,10 @@
 use std::collections::VecDeque;
 
 use super::constants::*;
-use super::error::{LispError, LispResult};
+use super::error::{ArgCount, LispError, LispResult};
+use super::primitive_eval::{builtin_print_env, builtin_the_environment};
 use super::types::*;
-use log::trace;
+use log::{trace, warn};
 
 macro_rules! special_form_p {
     ($vm:expr, $sym: expr) => {
@@ -13,10 +14,178 @@ macro_rules! special_form_p {
 
 impl VirtualMachine {
     pub fn evaluate(&mut self, exp: TypedPointer) -> LispResult<TypedPointer> {
+        // Each evaluation works on its own segment of the stack
+        let outer_base = std::mem::replace(&mut self.stack.base, self.stack.last);
+        let registers = self.registers.clone();
+        let step = self.step;
+        let result = self.ev_run(exp).map_err(|e| {
+            // The failed evaluation is reported even if recovering from it
+            // fails as well
+            let discarded = self.stack.last.saturating_sub(self.stack.base);
+            if let Err(recovery) = self.recover() {
+                warn!("recovery after failed evaluation: {}", recovery.message());
+            }
+            self.registers = registers;
+            e.with_discarded(discarded)
+        });
+        self.stack.base = outer_base;
+        self.step = step;
+
+        // Errors suspended by cleanup forms which exited some other way
+        // are never resumed
+        if self.stack.last == 0 {
+            self.pending_errors.clear();
+        }
+        result
+    }
+
+    /// Discards the stack segment and call frames of the current evaluation
+    /// and clears the registers, restoring dynamic bindings made along that segment.
+    /// Returns the number of stack entries discarded.
+    pub fn recover(&mut self) -> LispResult<usize> {
+        let base = self.stack.base;
+        let discarded = self.stack.last.saturating_sub(base);
+        let unwound = self.stack_unwind_to(base);
+
+        // Even if some binding could not be restored, the segment is gone
+        self.stack.last = base;
+        self.frame_prune(base);
+        self.registers = RegisterArea::default();
+
+        unwound.map(|_| discarded)
+    }
+
+    // Runs the steps of the evaluation until DONE is reached. Each step
+    // ends by scheduling the next one, so the Rust stack does not grow with
+    // the length of the evaluation.
+    fn ev_run(&mut self, exp: TypedPointer) -> LispResult<TypedPointer> {
+        let depth = self.stack.base;
         self.ev_pre_eval(exp);
-        self.ev_eval_dispatch()?;
 
-        Ok(self.registers.val.clone())
+        loop {
+            let mut result = match self.step {
+                EvalStep::Dispatch => self.ev_step_eval_dispatch(),
+                // Result is in registers.val
+                EvalStep::Continue if self.registers.cont == ConstSymbol::DONE => {
+                    self.frame_prune(self.stack.last);
+                    return Ok(self.registers.val.clone());
+                }
+                EvalStep::Continue => self.ev_step_continue(),
+            };
+
+            while let Err(mut e) = result {
+                // A throw which nothing catches is an error raised where it
+                // was thrown, so error handlers see it and cleanup forms
+                // run as they would for any other error
+                if e.thrown()
+                    .is_some_and(|(tag, _)| !self.ev_catch_p(&tag, depth))
+                {
+                    e = e.uncaught_throw();
+                }
+
+                if e.backtrace().is_empty() && e.thrown().is_none() {
+                    e = e.with_backtrace(self.backtrace());
+                }
+
+                match self.ev_find_exit_frame(&e, depth) {
+                    Some(frame) => result = self.ev_nonlocal_exit(e, frame),
+                    None => return Err(e),
+                }
+            }
+        }
+    }
+
+    // Finds the innermost frame which should intercept a non-local exit:
+    // either an unwind-protect frame, or the target of the exit itself
+    // (a catch frame with the thrown tag, or an error handler frame).
+    fn ev_find_exit_frame(&self, exit: &LispError, depth: usize) -> Option<usize> {
+        (depth..self.stack.last).rev().find(|&i| {
+            let ptr = &self.stack.area[i];
+            if *ptr == ConstSymbol::UNWIND_PROTECT_FRAME {
+                return true;
+            }
+
+            match exit.thrown() {
+                Some((tag, _)) => self.ev_catch_frame_p(i, &tag),
+                None => *ptr == ConstSymbol::ERROR_HANDLER_FRAME,
+            }
+        })
+    }
+
+    // Whether a catch frame for the tag is on the stack above `depth`
+    fn ev_catch_p(&self, tag: &TypedPointer, depth: usize) -> bool {
+        (depth..self.stack.last).any(|i| self.ev_catch_frame_p(i, tag))
+    }
+
+    // Catch frame: ( ... cont env tag CATCH_FRAME )
+    fn ev_catch_frame_p(&self, i: usize, tag: &TypedPointer) -> bool {
+        (self.stack.area[i] == ConstSymbol::CATCH_FRAME) && (self.stack.area[i - 1] == *tag)
+    }
+
+    fn ev_nonlocal_exit(&mut self, exit: LispError, frame: usize) -> LispResult<()> {
+        trace!("nonlocal_exit");
+        self.stack_unwind_to(frame + 1)?;
+        match self.stack_pop()? {
+            ConstSymbol::UNWIND_PROTECT_FRAME => self.ev_unwind_protect_exit(exit),
+            ConstSymbol::CATCH_FRAME => self.ev_throw(exit),
+            _ => self.ev_signal(exit),
+        }
+    }
+
+    // Applies the handler of the error handler frame on top of the stack
+    // to the Lisp-visible error object.
+    fn ev_signal(&mut self, error: LispError) -> LispResult<()> {
+        trace!("signal");
+        self.registers.exp = self.stack_pop()?;
+        self.registers.env = self.stack_pop()?;
+
+        let object = self.lisp_error_object(&error)?;
+        self.stack_push(object)?;
+        self.registers.cont = ConstSymbol::APPLY_ERROR_HANDLER;
+
+        self.ev_eval_dispatch()
+    }
+
+    // Returns the thrown value from the catch frame on top of the stack.
+    fn ev_throw(&mut self, exit: LispError) -> LispResult<()> {
+        trace!("throw");
+        let _tag = self.stack_pop()?;
+        self.registers.env = self.stack_pop()?;
+        self.registers.cont = self.stack_pop()?;
+        self.registers.val = exit.thrown().map(|(_, value)| value).unwrap_or_default();
+
+        self.ev_goto_continue_register()
+    }
+
+    // Runs the cleanup forms of the unwind-protect frame on top of the stack,
+    // saving the pending exit so it can be resumed afterwards.
+    fn ev_unwind_protect_exit(&mut self, exit: LispError) -> LispResult<()> {
+        trace!("unwind_protect_exit");
+        let cleanup = self.stack_pop()?;
+        self.registers.env = self.stack_pop()?;
+        let _cont = self.stack_pop()?;
+
+        // Pending exit: ( ... payload kind )
+        match exit.thrown() {
+            Some((tag, value)) => {
+                let payload = self.make_cons()?;
+                self.set_car(&payload, tag)?;
+                self.set_cdr(&payload, value)?;
+                self.stack_push(payload)?;
+                self.stack_push(ConstSymbol::THROW)?;
+            }
+            None => {
+                let object = self.lisp_error_object(&exit)?;
+                self.stack_push(object.clone())?;
+                self.stack_push(ConstSymbol::ERROR)?;
+                self.pending_errors.push((object, exit));
+            }
+        }
+
+        self.registers.exp = EvalHelper::prepare_multiple_list_eval(self, cleanup)?;
+        self.registers.cont = ConstSymbol::RESUME_UNWIND;
+
+        self.ev_eval_dispatch()
     }
 
     fn ev_pre_eval(&mut self, exp: TypedPointer) {
@@ -24,14 +193,25 @@ impl VirtualMachine {
         self.registers.env = ConstSymbol::E0;
         self.registers.cont = ConstSymbol::DONE;
         self.registers.val = TypedPointer::default();
+        self.step = EvalStep::Dispatch;
     }
 
     fn ev_eval_dispatch(&mut self) -> LispResult<()> {
+        self.step = EvalStep::Dispatch;
+        Ok(())
+    }
+
+    fn ev_goto_continue_register(&mut self) -> LispResult<()> {
+        self.step = EvalStep::Continue;
+        Ok(())
+    }
+
+    fn ev_step_eval_dispatch(&mut self) -> LispResult<()> {
         trace!("eval_dispatch");
         let exp = self.registers.exp.clone();
         match exp.tag {
             // Self-evaluating expressions
-            DataType::Number | DataType::Literal => self.ev_self_eval(),
+            DataType::Number | DataType::Literal | DataType::String => self.ev_self_eval(),
 
             // Variables
             DataType::Atom => self.ev_variable(),
@@ -40,15 +220,31 @@ impl VirtualMachine {
                 // Special Forms
                 if special_form_p!(self, ConstSymbol::SETQ) {
                     self.ev_setq()
+                } else if special_form_p!(self, ConstSymbol::DEFVAR) {
+                    self.ev_defvar(false)
+                } else if special_form_p!(self, ConstSymbol::DEFPARAMETER) {
+                    self.ev_defvar(true)
+                } else if special_form_p!(self, ConstSymbol::CATCH_ERROR) {
+                    self.ev_catch_error()
+                } else if special_form_p!(self, ConstSymbol::CATCH) {
+                    self.ev_catch()
+                } else if special_form_p!(self, ConstSymbol::UNWIND_PROTECT) {
+                    self.ev_unwind_protect()
+                } else if special_form_p!(self, ConstSymbol::IF) {
+                    self.ev_if()
                 } else if special_form_p!(self, ConstSymbol::QUOTE) {
                     self.ev_quote()
+                } else if special_form_p!(self, ConstSymbol::QUASIQUOTE) {
+                    self.ev_quasiquote()
                 } else if special_form_p!(self, ConstSymbol::FN) {
-                    self.ev_fn()
+                    self.ev_fn(DataType::Function)
+                } else if special_form_p!(self, ConstSymbol::MAC) {
+                    self.ev_fn(DataType::Macro)
                 } else if special_form_p!(self, ConstSymbol::DO) {
                     self.ev_do()
                 } else {
                     // Application
-                    if EvalHelper::applicationp(&self, exp)? {
+                    if EvalHelper::applicationp(exp) {
                         self.ev_application()
                     } else {
                         self.ev_expression_error()
@@ -59,9 +255,42 @@ impl VirtualMachine {
     }
 
     fn ev_do(&mut self) -> LispResult<()> {
-        Err(LispError::internal(
-            "unimplemented evaluation for special form DO",
-        ))
+        trace!("do");
+        self.registers.unev = self.get_cdr(&self.registers.exp.clone())?;
+
+        if self.registers.unev == ConstSymbol::NIL {
+            self.registers.val = ConstSymbol::NIL;
+            return self.ev_goto_continue_register();
+        }
+
+        self.stack_push(self.registers.cont.clone())?;
+
+        self.ev_sequence()
+    }
+
+    fn ev_sequence(&mut self) -> LispResult<()> {
+        trace!("sequence");
+        self.registers.exp = self.get_car(&self.registers.unev.clone())?;
+
+        if EvalHelper::last_operand_p(self, self.registers.unev.clone())? {
+            self.registers.cont = self.stack_pop()?;
+            return self.ev_eval_dispatch();
+        }
+
+        self.stack_push(self.registers.unev.clone())?;
+        self.stack_push(self.registers.env.clone())?;
+        self.registers.cont = ConstSymbol::EVAL_SEQUENCE_CONTINUE;
+
+        self.ev_eval_dispatch()
+    }
+
+    fn ev_sequence_continue(&mut self) -> LispResult<()> {
+        trace!("sequence_continue");
+        self.registers.env = self.stack_pop()?;
+        self.registers.unev = self.stack_pop()?;
+        self.registers.unev = self.get_cdr(&self.registers.unev.clone())?;
+
+        self.ev_sequence()
     }
 
     fn ev_self_eval(&mut self) -> LispResult<()> {
@@ -89,6 +318,87 @@ impl VirtualMachine {
         self.ev_eval_dispatch()
     }
 
+    // Declares a special variable. DEFPARAMETER always assigns the initial
+    // value, while DEFVAR only assigns it when the variable is unbound.
+    fn ev_defvar(&mut self, always_assign: bool) -> LispResult<()> {
+        trace!("defvar");
+        let symbol = self.get_cadr(&self.registers.exp.clone())?;
+        self.declare_special(symbol.clone())?;
+
+        let value_exp = self.get_cdr(&self.get_cdr(&self.registers.exp.clone())?)?;
+        let unbound = self.lookup_atom_value(symbol.clone())?.tag == DataType::Undefined;
+
+        if (value_exp == ConstSymbol::NIL) || !(always_assign || unbound) {
+            self.registers.val = symbol;
+            return self.ev_goto_continue_register();
+        }
+
+        self.stack_push(self.registers.cont.clone())?;
+        self.stack_push(symbol)?;
+        self.registers.exp = self.get_car(&value_exp)?;
+        self.registers.cont = ConstSymbol::EVAL_DEFINE;
+
+        self.ev_eval_dispatch()
+    }
+
+    // Evaluates a form with an error handler, which is applied to the error
+    // object if an error is signalled during evaluation.
+    fn ev_catch_error(&mut self) -> LispResult<()> {
+        trace!("catch_error");
+        // Handler frame: ( ... cont env handler ERROR_HANDLER_FRAME )
+        self.stack_push(self.registers.cont.clone())?;
+        self.stack_push(self.registers.env.clone())?;
+        self.stack_push(self.get_caddr(&self.registers.exp.clone())?)?;
+        self.stack_push(ConstSymbol::ERROR_HANDLER_FRAME)?;
+        self.registers.exp = self.get_cadr(&self.registers.exp.clone())?;
+        self.registers.cont = ConstSymbol::POP_ERROR_HANDLER;
+
+        self.ev_eval_dispatch()
+    }
+
+    // Evaluates the tag of a catch form, then evaluates its body in a catch
+    // frame for that tag.
+    fn ev_catch(&mut self) -> LispResult<()> {
+        trace!("catch");
+        self.stack_push(self.registers.cont.clone())?;
+        self.stack_push(self.registers.env.clone())?;
+        self.stack_push(self.get_cdr(&self.get_cdr(&self.registers.exp.clone())?)?)?;
+        self.registers.exp = self.get_cadr(&self.registers.exp.clone())?;
+        self.registers.cont = ConstSymbol::CATCH_TAG_EVALUATED;
+
+        self.ev_eval_dispatch()
+    }
+
+    fn ev_catch_tag_evaluated(&mut self) -> LispResult<()> {
+        trace!("catch_tag_evaluated");
+        self.registers.unev = self.stack_pop()?;
+        self.registers.env = self.stack_pop()?;
+        // Catch frame: ( ... cont env tag CATCH_FRAME )
+        self.stack_push(self.registers.env.clone())?;
+        self.stack_push(self.registers.val.clone())?;
+        self.stack_push(ConstSymbol::CATCH_FRAME)?;
+        self.registers.exp =
+            EvalHelper::prepare_multiple_list_eval(self, self.registers.unev.clone())?;
+        self.registers.cont = ConstSymbol::POP_CATCH;
+
+        self.ev_eval_dispatch()
+    }
+
+    // Evaluates a protected form, guaranteeing that the cleanup forms are
+    // evaluated afterwards, even on non-local exits.
+    fn ev_unwind_protect(&mut self) -> LispResult<()> {
+        trace!("unwind_protect");
+        // Unwind-protect frame: ( ... cont env cleanup UNWIND_PROTECT_FRAME )
+        self.stack_push(self.registers.cont.clone())?;
+        self.stack_push(self.registers.env.clone())?;
+        self.stack_push(self.get_cdr(&self.get_cdr(&self.registers.exp.clone())?)?)?;
+        self.stack_push(ConstSymbol::UNWIND_PROTECT_FRAME)?;
+        self.registers.exp = self.get_cadr(&self.registers.exp.clone())?;
+        self.registers.cont = ConstSymbol::UNWIND_PROTECT_CLEANUP;
+
+        self.ev_eval_dispatch()
+    }
+
     // ev-fn
     // (push (fetch unev))
     // (assign val (cons (fetch env) nil))
@@ -96,7 +406,9 @@ impl VirtualMachine {
     // (assign val (cons (fetch unev) (fetch val)))
     // (assign val (ptr 'function (untype (fetch val))))
     // (pop unev)
-    fn ev_fn(&mut self) -> LispResult<()> {
+    //
+    // Macros are built the same way, only tagged as macros.
+    fn ev_fn(&mut self, tag: DataType) -> LispResult<()> {
         trace!("fn");
         self.stack_push(self.registers.unev.clone())?;
 
@@ -112,7 +424,7 @@ impl VirtualMachine {
         self.set_cdr(&cons2, self.registers.val.clone())?;
         self.registers.val = cons2;
 
-        let ptr = TypedPointer::new(DataType::Function, self.registers.val.value);
+        let ptr = TypedPointer::new(tag, self.registers.val.value);
         self.registers.val = ptr;
 
         self.registers.unev = self.stack_pop()?;
@@ -127,9 +439,62 @@ impl VirtualMachine {
         self.ev_goto_continue_register()
     }
 
+    // Evaluates the predicate of an if form, then one of its branches in
+    // tail position. A missing else branch evaluates to nil.
+    fn ev_if(&mut self) -> LispResult<()> {
+        trace!("if");
+        let received = self.list_to_vec(self.registers.exp.clone())?.len() - 1;
+        if !(2..=3).contains(&received) {
+            return Err(LispError::wrong_arg_count(
+                "if".to_owned(),
+                ArgCount::Between(2, 3),
+                received,
+            ));
+        }
+
+        self.stack_push(self.registers.cont.clone())?;
+        self.stack_push(self.registers.env.clone())?;
+        self.stack_push(self.registers.exp.clone())?;
+        self.registers.exp = self.get_cadr(&self.registers.exp.clone())?;
+        self.registers.cont = ConstSymbol::IF_DECIDE;
+
+        self.ev_eval_dispatch()
+    }
+
+    fn ev_if_decide(&mut self) -> LispResult<()> {
+        trace!("if_decide");
+        let exp = self.stack_pop()?;
+        self.registers.env = self.stack_pop()?;
+        self.registers.cont = self.stack_pop()?;
+
+        // (if pred consequent . alternative)
+        let branches = self.get_cdr(&self.get_cdr(&exp)?)?;
+        let alternative = self.get_cdr(&branches)?;
+        self.registers.exp = if self.registers.val != ConstSymbol::NIL {
+            self.get_car(&branches)?
+        } else if alternative != ConstSymbol::NIL {
+            self.get_car(&alternative)?
+        } else {
+            ConstSymbol::NIL
+        };
+
+        self.ev_eval_dispatch()
+    }
+
+    // Rewrites a quasiquoted template into the expression which builds it,
+    // then evaluates that expression in its place.
+    fn ev_quasiquote(&mut self) -> LispResult<()> {
+        trace!("quasiquote");
+        let template = self.get_cadr(&self.registers.exp.clone())?;
+        self.registers.exp = self.quasiquote_expand(template, 1)?;
+
+        self.ev_eval_dispatch()
+    }
+
     fn ev_application(&mut self) -> LispResult<()> {
         trace!("application");
         self.registers.unev = self.get_cdr(&self.registers.exp.clone())?;
+        self.frame_push(self.registers.exp.clone());
         self.registers.exp = self.get_car(&self.registers.exp.clone())?;
         self.stack_push(self.registers.cont.clone())?;
         self.stack_push(self.registers.env.clone())?;
@@ -139,38 +504,83 @@ impl VirtualMachine {
         self.ev_eval_dispatch()
     }
 
-    fn ev_goto_continue_register(&mut self) -> LispResult<()> {
+    fn ev_step_continue(&mut self) -> LispResult<()> {
         trace!("goto_continue_register");
+        // Applications deeper on the stack have returned by now
+        self.frame_prune(self.stack.last);
         let cont = self.registers.cont.clone();
         match cont {
-            ConstSymbol::DONE => self.ev_done(),
             ConstSymbol::EVAL_ARGS => self.ev_eval_args(),
             ConstSymbol::ACCUMULATE_ARG => self.ev_accumulate_arg(),
             ConstSymbol::ACCUMULATE_LAST_ARG => self.ev_accumulate_last_arg(),
             ConstSymbol::EVAL_ASSIGN => self.ev_eval_assign(),
+            ConstSymbol::EVAL_DEFINE => self.ev_eval_define(),
+            ConstSymbol::UNBIND_DYNAMIC => self.ev_unbind_dynamic(),
+            ConstSymbol::EVAL_SEQUENCE_CONTINUE => self.ev_sequence_continue(),
+            ConstSymbol::POP_ERROR_HANDLER => self.ev_pop_error_handler(),
+            ConstSymbol::APPLY_ERROR_HANDLER => self.ev_apply_error_handler(),
+            ConstSymbol::CATCH_TAG_EVALUATED => self.ev_catch_tag_evaluated(),
+            ConstSymbol::POP_CATCH => self.ev_pop_catch(),
+            ConstSymbol::UNWIND_PROTECT_CLEANUP => self.ev_unwind_protect_cleanup(),
+            ConstSymbol::UNWIND_PROTECT_RETURN => self.ev_unwind_protect_return(),
+            ConstSymbol::RESUME_UNWIND => self.ev_resume_unwind(),
+            ConstSymbol::IF_DECIDE => self.ev_if_decide(),
+            ConstSymbol::EVAL_EXPANSION => self.ev_eval_expansion(),
             _ => self.ev_expression_error(),
         }
     }
 
-    fn ev_done(&mut self) -> LispResult<()> {
-        trace!("done");
-        // Result is in registers.val
-        Ok(())
-    }
-
     fn ev_eval_args(&mut self) -> LispResult<()> {
         trace!("eval_args");
         self.registers.unev = self.stack_pop()?;
         self.registers.env = self.stack_pop()?;
         self.registers.fun = self.registers.val.clone();
+        if EvalHelper::macro_p(&self.registers.fun) {
+            return self.ev_macro_expand();
+        }
+
         self.stack_push(self.registers.fun.clone())?;
         self.registers.argl = ConstSymbol::NIL;
 
         self.ev_eval_arg_loop()
     }
 
+    // Applies a macro to the unevaluated operands of the application. Its
+    // expansion is evaluated afterwards on the environment of the
+    // application, in place of it.
+    fn ev_macro_expand(&mut self) -> LispResult<()> {
+        trace!("macro_expand");
+        self.frame_set_function(self.registers.fun.clone());
+        self.stack_push(self.registers.env.clone())?;
+
+        let (lambda_list, body, env) = EvalHelper::get_fn_parts(self, self.registers.fun.clone())?;
+        let operands = self.list_to_vec(self.registers.unev.clone())?;
+
+        self.registers.exp = EvalHelper::prepare_multiple_list_eval(self, body)?;
+        let (new_env, specials) = self.ev_make_bindings(lambda_list, &operands, env)?;
+        self.registers.env = new_env;
+        self.registers.cont = ConstSymbol::EVAL_EXPANSION;
+
+        self.ev_push_dynamic_frame(specials)?;
+
+        self.ev_eval_dispatch()
+    }
+
+    fn ev_eval_expansion(&mut self) -> LispResult<()> {
+        trace!("eval_expansion");
+        self.registers.env = self.stack_pop()?;
+        self.registers.cont = self.stack_pop()?;
+        self.registers.exp = self.registers.val.clone();
+
+        self.ev_eval_dispatch()
+    }
+
     fn ev_eval_arg_loop(&mut self) -> LispResult<()> {
         trace!("eval_arg_loop");
+        // Built-ins which inspect the environment need it preserved across
+        // their last argument too, which is otherwise not done. So do apply
+        // and funcall, which may be applying one of them
+        let keep_env = EvalHelper::keeps_environment_p(&self.stack_peek()?);
         self.stack_push(self.registers.argl.clone())?;
 
         if self.registers.unev.clone() == ConstSymbol::NIL {
@@ -182,7 +592,7 @@ impl VirtualMachine {
 
         self.registers.exp = self.get_car(&self.registers.unev.clone())?;
 
-        if EvalHelper::last_operand_p(&self, self.registers.unev.clone())? {
+        if !keep_env && EvalHelper::last_operand_p(self, self.registers.unev.clone())? {
             self.ev_eval_last_arg()
         } else {
             self.stack_push(self.registers.env.clone())?;
@@ -232,12 +642,112 @@ impl VirtualMachine {
         self.registers.exp = self.registers.val.clone();
         self.registers.val = self.stack_pop()?;
         self.assign_value(self.registers.val.clone(), self.registers.exp.clone())?;
+        self.name_function(&self.registers.exp.clone(), &self.registers.val.clone())?;
         self.registers.val = self.registers.exp.clone();
         self.registers.cont = self.stack_pop()?;
 
         self.ev_goto_continue_register()
     }
 
+    fn ev_eval_define(&mut self) -> LispResult<()> {
+        trace!("eval_define");
+        let symbol = self.stack_pop()?;
+        self.assign_value(symbol.clone(), self.registers.val.clone())?;
+        self.name_function(&self.registers.val.clone(), &symbol)?;
+        self.registers.val = symbol;
+        self.registers.cont = self.stack_pop()?;
+
+        self.ev_goto_continue_register()
+    }
+
+    // Restores dynamic bindings after evaluating the body of a function
+    // which bound special variables.
+    fn ev_unbind_dynamic(&mut self) -> LispResult<()> {
+        trace!("unbind_dynamic");
+        let _marker = self.stack_pop()?;
+        let symbols = self.stack_pop()?;
+        self.dynamic_unbind_list(symbols)?;
+        self.registers.cont = self.stack_pop()?;
+
+        self.ev_goto_continue_register()
+    }
+
+    fn ev_pop_error_handler(&mut self) -> LispResult<()> {
+        trace!("pop_error_handler");
+        let _marker = self.stack_pop()?;
+        let _handler = self.stack_pop()?;
+        self.registers.env = self.stack_pop()?;
+        self.registers.cont = self.stack_pop()?;
+
+        self.ev_goto_continue_register()
+    }
+
+    fn ev_apply_error_handler(&mut self) -> LispResult<()> {
+        trace!("apply_error_handler");
+        self.registers.fun = self.registers.val.clone();
+        let object = self.stack_pop()?;
+        let argl = self.make_cons()?;
+        self.set_car(&argl, object)?;
+        self.set_cdr(&argl, ConstSymbol::NIL)?;
+        self.registers.argl = argl;
+
+        self.ev_apply_dispatch()
+    }
+
+    fn ev_pop_catch(&mut self) -> LispResult<()> {
+        trace!("pop_catch");
+        let _marker = self.stack_pop()?;
+        let _tag = self.stack_pop()?;
+        self.registers.env = self.stack_pop()?;
+        self.registers.cont = self.stack_pop()?;
+
+        self.ev_goto_continue_register()
+    }
+
+    // Protected form returned normally; evaluate cleanup forms, saving the
+    // returned value.
+    fn ev_unwind_protect_cleanup(&mut self) -> LispResult<()> {
+        trace!("unwind_protect_cleanup");
+        let _marker = self.stack_pop()?;
+        let cleanup = self.stack_pop()?;
+        self.registers.env = self.stack_pop()?;
+        self.stack_push(self.registers.val.clone())?;
+        self.registers.exp = EvalHelper::prepare_multiple_list_eval(self, cleanup)?;
+        self.registers.cont = ConstSymbol::UNWIND_PROTECT_RETURN;
+
+        self.ev_eval_dispatch()
+    }
+
+    fn ev_unwind_protect_return(&mut self) -> LispResult<()> {
+        trace!("unwind_protect_return");
+        self.registers.val = self.stack_pop()?;
+        self.registers.cont = self.stack_pop()?;
+
+        self.ev_goto_continue_register()
+    }
+
+    // Cleanup forms ran during a non-local exit; resume it.
+    fn ev_resume_unwind(&mut self) -> LispResult<()> {
+        trace!("resume_unwind");
+        let kind = self.stack_pop()?;
+        let payload = self.stack_pop()?;
+
+        if kind == ConstSymbol::THROW {
+            let tag = self.get_car(&payload)?;
+            let value = self.get_cdr(&payload)?;
+            let tag_name = crate::printer::format_object(self, &tag);
+            Err(LispError::throw(tag, value, tag_name))
+        } else {
+            // The original error is raised again, keeping its kind
+            let pending = self
+                .pending_errors
+                .iter()
+                .rposition(|(object, _)| *object == payload)
+                .ok_or(LispError::internal("resumed unknown error"))?;
+            Err(self.pending_errors.remove(pending).1)
+        }
+    }
+
     fn ev_expression_error(&mut self) -> LispResult<()> {
         trace!("expression_error");
         Err(LispError::internal("expression error"))
@@ -249,14 +759,203 @@ impl VirtualMachine {
         trace!("apply_dispatch");
 
         let fun = self.registers.fun.clone();
-
-        if EvalHelper::primitive_function_p(fun.clone()) {
+        self.frame_set_function(fun.clone());
+
+        if fun == ConstSymbol::BIN_EVAL {
+            self.ev_eval()
+        } else if fun == ConstSymbol::BIN_CALL_CC {
+            self.ev_call_cc()
+        } else if fun == ConstSymbol::BIN_APPLY {
+            self.ev_apply(true)
+        } else if fun == ConstSymbol::BIN_FUNCALL {
+            self.ev_apply(false)
+        } else if fun == ConstSymbol::BIN_LOAD {
+            self.ev_load()
+        } else if EvalHelper::environment_function_p(&fun) {
+            self.ev_environment_fn_apply()
+        } else if EvalHelper::primitive_function_p(fun.clone()) {
             self.ev_primitive_fn_apply()
         } else if EvalHelper::compound_function_p(fun.clone()) {
             self.ev_compound_fn_apply()
+        } else if self.is_continuation(&fun) {
+            self.ev_continuation_apply()
         } else {
-            Err(LispError::internal("unknown function type"))
+            Err(self.not_a_function(&fun))
+        }
+    }
+
+    // Evaluates the first argument on the environment given as second
+    // argument, or on E0 if none was given. The evaluation replaces the
+    // application, so `cont` is popped and the form runs in tail position.
+    fn ev_eval(&mut self) -> LispResult<()> {
+        trace!("eval");
+        let mut args = self.ev_arguments()?;
+        if args.is_empty() || (args.len() > 2) {
+            return Err(LispError::wrong_arg_count(
+                "eval".to_owned(),
+                ArgCount::Between(1, 2),
+                args.len(),
+            ));
+        }
+
+        let env = args.get(1).cloned().unwrap_or(ConstSymbol::E0);
+        if env.tag != DataType::Environment {
+            return Err(self.type_error("environment", &env));
         }
+
+        self.registers.exp = args.swap_remove(0);
+        self.registers.env = env;
+        self.registers.cont = self.stack_pop()?;
+
+        self.ev_eval_dispatch()
+    }
+
+    // Applies the first argument to the remaining arguments. When spreading,
+    // the last argument must be a list holding the final arguments, as in
+    // (apply f 1 2 '(3 4)). The application stays in tail position, since
+    // `cont` is still on top of the stack.
+    fn ev_apply(&mut self, spread: bool) -> LispResult<()> {
+        trace!("apply");
+        let name = if spread { "apply" } else { "funcall" };
+
+        let mut args = self.ev_arguments()?;
+        if args.is_empty() || (spread && args.len() < 2) {
+            let expected = ArgCount::AtLeast(if spread { 2 } else { 1 });
+            return Err(LispError::wrong_arg_count(
+                name.to_owned(),
+                expected,
+                args.len(),
+            ));
+        }
+
+        if spread {
+            let last = args.pop().unwrap_or_default();
+            if (last != ConstSymbol::NIL) && (last.tag != DataType::Cons) {
+                return Err(self.type_error("list", &last));
+            }
+            args.extend(self.list_to_vec(last)?);
+        }
+
+        let fun = args.remove(0);
+        args.reverse();
+        self.registers.argl = self.make_list(&args)?;
+        self.registers.fun = fun;
+
+        self.ev_apply_dispatch()
+    }
+
+    // Evaluates the forms of a source file on E0, in tail position like
+    // `eval`. The file is pushed onto *load-files* for the extent of the
+    // evaluation, which both resolves nested relative loads and detects
+    // cycles.
+    fn ev_load(&mut self) -> LispResult<()> {
+        trace!("load");
+        let args = self.list_to_vec(self.registers.argl.clone())?;
+        if args.len() != 1 {
+            return Err(LispError::wrong_arg_count(
+                "load".to_owned(),
+                ArgCount::Exactly(1),
+                args.len(),
+            ));
+        }
+
+        let name = &args[0];
+        if name.tag != DataType::String {
+            return Err(self.type_error("string", name));
+        }
+
+        let path = self.resolve_load_path(&self.strings.area[name.value].clone())?;
+        let path_name = path.display().to_string();
+        let loading = self.loading_files()?;
+        if loading.contains(&path_name) {
+            let chain: Vec<&str> = std::iter::once(path_name.as_str())
+                .chain(loading.iter().map(String::as_str))
+                .rev()
+                .collect();
+            return Err(LispError::load(format!(
+                "cyclic load: {}",
+                chain.join(" -> ")
+            )));
+        }
+
+        self.registers.exp = self.read_source_file(&path)?;
+
+        let files = self.lookup_atom_value(ConstSymbol::LOAD_FILES)?;
+        let file = self.make_string(&path_name)?;
+        let cell = self.make_cons()?;
+        self.set_car(&cell, file)?;
+        self.set_cdr(&cell, files)?;
+        self.dynamic_bind(ConstSymbol::LOAD_FILES, cell)?;
+        let specials = self.make_list(&[ConstSymbol::LOAD_FILES])?;
+
+        self.registers.env = ConstSymbol::E0;
+        self.registers.cont = self.stack_pop()?;
+
+        self.ev_push_dynamic_frame(specials)?;
+
+        self.ev_eval_dispatch()
+    }
+
+    // Applies a function to the continuation of the call/cc application,
+    // whose `cont` is still on top of the stack.
+    fn ev_call_cc(&mut self) -> LispResult<()> {
+        trace!("call_cc");
+        let received = self.list_to_vec(self.registers.argl.clone())?.len();
+        if received != 1 {
+            return Err(LispError::wrong_arg_count(
+                "call/cc".to_owned(),
+                ArgCount::Exactly(1),
+                received,
+            ));
+        }
+
+        let cont = self.stack_peek()?;
+        let continuation = self.capture_continuation(cont, self.stack.last - 1)?;
+
+        self.registers.fun = self.get_car(&self.registers.argl.clone())?;
+        let argl = self.make_cons()?;
+        self.set_car(&argl, continuation)?;
+        self.set_cdr(&argl, ConstSymbol::NIL)?;
+        self.registers.argl = argl;
+
+        self.ev_apply_dispatch()
+    }
+
+    fn ev_continuation_apply(&mut self) -> LispResult<()> {
+        trace!("continuation_apply");
+        let argl = self.registers.argl.clone();
+        self.registers.val = if argl == ConstSymbol::NIL {
+            ConstSymbol::NIL
+        } else if self.get_cdr(&argl)? == ConstSymbol::NIL {
+            self.get_car(&argl)?
+        } else {
+            return Err(LispError::wrong_arg_count(
+                "continuation".to_owned(),
+                ArgCount::Between(0, 1),
+                self.list_to_vec(argl)?.len(),
+            ));
+        };
+
+        self.registers.cont = self.reinstate_continuation(&self.registers.fun.clone())?;
+
+        self.ev_goto_continue_register()
+    }
+
+    // Applies a built-in which inspects the environment of the application,
+    // still on ENV since the argument loop preserved it.
+    fn ev_environment_fn_apply(&mut self) -> LispResult<()> {
+        trace!("environment_fn_apply");
+        let args = self.ev_arguments()?;
+
+        let env = self.registers.env.clone();
+        self.registers.val = if self.registers.fun == ConstSymbol::BIN_THE_ENVIRONMENT {
+            builtin_the_environment(env, &args)?
+        } else {
+            builtin_print_env(self, env, &args)?
+        };
+        self.registers.cont = self.stack_pop()?;
+
+        self.ev_goto_continue_register()
     }
 
     fn ev_primitive_fn_apply(&mut self) -> LispResult<()> {
@@ -271,17 +970,17 @@ impl VirtualMachine {
     fn ev_compound_fn_apply(&mut self) -> LispResult<()> {
         trace!("compound_fn_apply");
 
-        let (lambda_list, body, env) = EvalHelper::get_fn_parts(&self, self.registers.fun.clone())?;
+        let (lambda_list, body, env) = EvalHelper::get_fn_parts(self, self.registers.fun.clone())?;
 
         self.registers.exp = EvalHelper::prepare_multiple_list_eval(self, body)?;
 
-        self.registers.env = self.ev_make_bindings(
-            lambda_list.clone(),
-            self.registers.argl.clone(),
-            env.clone(),
-        )?;
+        let args = self.ev_arguments()?;
+        let (new_env, specials) = self.ev_make_bindings(lambda_list, &args, env)?;
+        self.registers.env = new_env;
         self.registers.cont = self.stack_pop()?;
 
+        self.ev_push_dynamic_frame(specials)?;
+
         self.ev_eval_dispatch()
     }
 
@@ -293,7 +992,7 @@ impl VirtualMachine {
         trace!("apply_primitive_fn");
         // apply primitive fn to list of arguments.
         // Invert ARGL into vector
-        let argl = {
+        let mut argl = {
             let mut v = VecDeque::new();
             let mut argl = argl.clone();
 
@@ -306,49 +1005,170 @@ impl VirtualMachine {
             v
         };
 
-        self.dispatch_prim_eval(fun, &argl.as_slices().0)
+        // Pushing to the front may wrap the arguments around the buffer
+        self.dispatch_prim_eval(fun, argl.make_contiguous())
+    }
+
+    /// Arguments of the application on ARGL, in order. ARGL is inverted,
+    /// so the last argument comes first on it.
+    fn ev_arguments(&self) -> LispResult<Vec<TypedPointer>> {
+        let mut args = self.list_to_vec(self.registers.argl.clone())?;
+        args.reverse();
+        Ok(args)
     }
 
+    /// Pushes a frame which unbinds the given special variables before
+    /// going on to CONT. Nothing is pushed when there are none.
+    fn ev_push_dynamic_frame(&mut self, specials: TypedPointer) -> LispResult<()> {
+        if specials == ConstSymbol::NIL {
+            return Ok(());
+        }
+
+        // Dynamic frame: ( ... cont specials DYNAMIC_FRAME )
+        self.stack_push(self.registers.cont.clone())?;
+        self.stack_push(specials)?;
+        self.stack_push(ConstSymbol::DYNAMIC_FRAME)?;
+        self.registers.cont = ConstSymbol::UNBIND_DYNAMIC;
+        Ok(())
+    }
+
+    /// Binds the arguments to a new environment, returning it alongside the
+    /// list of special variables which were dynamically bound instead. A
+    /// dotted lambda list, or a lone symbol, binds its last symbol to the
+    /// list of remaining arguments.
     fn ev_make_bindings(
         &mut self,
         lambda_list: TypedPointer,
-        argl: TypedPointer,
+        args: &[TypedPointer],
         env: TypedPointer,
-    ) -> LispResult<TypedPointer> {
+    ) -> LispResult<(TypedPointer, TypedPointer)> {
         trace!("make_bindings");
 
-        // Invert ARGL into vector
-        let mut argl_inv = VecDeque::new();
-        let mut lambda_list_vec = VecDeque::new();
+        let mut params = vec![];
+        let mut rest = lambda_list;
+        while rest.tag == DataType::Cons {
+            params.push(self.get_car(&rest)?);
+            rest = self.get_cdr(&rest)?;
+        }
 
-        // TODO: These bindings do not consider special cases yet.
-        // For now, arity does not change!
-        let bindings = {
-            let mut argl = argl.clone();
-            let mut ll = lambda_list.clone();
+        let arity_ok = if rest == ConstSymbol::NIL {
+            args.len() == params.len()
+        } else {
+            args.len() >= params.len()
+        };
+        if !arity_ok {
+            return Err(LispError::wrong_arg_count(
+                crate::printer::format_object(self, &self.registers.fun),
+                if rest == ConstSymbol::NIL {
+                    ArgCount::Exactly(params.len())
+                } else {
+                    ArgCount::AtLeast(params.len())
+                },
+                args.len(),
+            ));
+        }
 
-            while argl != ConstSymbol::NIL {
-                let car = self.get_car(&argl.clone())?;
-                argl = self.get_cdr(&argl.clone())?;
-                argl_inv.push_back(car);
+        let mut bindings: Vec<(TypedPointer, TypedPointer)> =
+            params.into_iter().zip(args.iter().cloned()).collect();
+        if rest != ConstSymbol::NIL {
+            let remaining = self.make_list(&args[bindings.len()..])?;
+            bindings.push((rest, remaining));
+        }
+
+        let new_env = self.make_environment(env)?;
+        let mut specials = ConstSymbol::NIL;
+
+        for (symbol, value) in bindings {
+            if symbol.tag != DataType::Atom {
+                return Err(self.type_error("symbol", &symbol));
             }
 
-            while ll != ConstSymbol::NIL {
-                let car = self.get_car(&ll.clone())?;
-                ll = self.get_cdr(&ll.clone())?;
-                lambda_list_vec.push_front(car);
+            if self.is_special(&symbol) {
+                self.dynamic_bind(symbol.clone(), value)?;
+                let cons = self.make_cons()?;
+                self.set_car(&cons, symbol)?;
+                self.set_cdr(&cons, specials)?;
+                specials = cons;
+            } else {
+                self.env_bind(new_env.clone(), symbol, value)?;
             }
+        }
 
-            lambda_list_vec.iter().zip(argl_inv.iter())
-        };
+        Ok((new_env, specials))
+    }
 
-        let new_env = self.make_environment(env)?;
+    /// Rewrites a quasiquoted template into an expression which builds it.
+    /// Unquoted expressions are evaluated, spliced ones are appended to the
+    /// rest of their list, and nested quasiquotes only have the unquotes of
+    /// their own level rewritten. The built-ins are quoted, so that the
+    /// expansion does not depend on the bindings of `cons` or `append`.
+    fn quasiquote_expand(
+        &mut self,
+        template: TypedPointer,
+        depth: usize,
+    ) -> LispResult<TypedPointer> {
+        if !self.quasiquote_unquoted_p(&template)? {
+            return self.make_list(&[ConstSymbol::QUOTE, template]);
+        }
 
-        for (symbol, value) in bindings {
-            self.env_bind(new_env.clone(), symbol.clone(), value.clone())?;
+        let head = self.get_car(&template)?;
+        if (head == ConstSymbol::UNQUOTE) || (head == ConstSymbol::UNQUOTE_SPLICE) {
+            let exp = self.get_cadr(&template)?;
+            if depth == 1 {
+                return Ok(exp);
+            }
+
+            let exp = self.quasiquote_expand(exp, depth - 1)?;
+            return self.quasiquote_list(&[head, exp]);
+        }
+
+        if head == ConstSymbol::QUASIQUOTE {
+            let exp = self.get_cadr(&template)?;
+            let exp = self.quasiquote_expand(exp, depth + 1)?;
+            return self.quasiquote_list(&[head, exp]);
+        }
+
+        let rest = self.get_cdr(&template)?;
+        let rest = self.quasiquote_expand(rest, depth)?;
+        let spliced = (depth == 1)
+            && (head.tag == DataType::Cons)
+            && (self.get_car(&head)? == ConstSymbol::UNQUOTE_SPLICE);
+
+        if spliced {
+            let exp = self.get_cadr(&head)?;
+            let append = self.make_list(&[ConstSymbol::QUOTE, ConstSymbol::BIN_APPEND])?;
+            self.make_list(&[append, exp, rest])
+        } else {
+            let head = self.quasiquote_expand(head, depth)?;
+            let cons = self.make_list(&[ConstSymbol::QUOTE, ConstSymbol::BIN_CONS])?;
+            self.make_list(&[cons, head, rest])
+        }
+    }
+
+    // Expression building a two-element list of a quoted symbol and the
+    // value of an expression.
+    fn quasiquote_list(&mut self, parts: &[TypedPointer; 2]) -> LispResult<TypedPointer> {
+        let list = self.make_list(&[ConstSymbol::QUOTE, ConstSymbol::BIN_LIST])?;
+        let symbol = self.make_list(&[ConstSymbol::QUOTE, parts[0].clone()])?;
+        self.make_list(&[list, symbol, parts[1].clone()])
+    }
+
+    // Whether a template holds unquotes anywhere, so that templates which
+    // do not can be quoted as they are.
+    fn quasiquote_unquoted_p(&self, template: &TypedPointer) -> LispResult<bool> {
+        let mut ptr = template.clone();
+        while ptr.tag == DataType::Cons {
+            let head = self.get_car(&ptr)?;
+            if (head == ConstSymbol::UNQUOTE)
+                || (head == ConstSymbol::UNQUOTE_SPLICE)
+                || self.quasiquote_unquoted_p(&head)?
+            {
+                return Ok(true);
+            }
+            ptr = self.get_cdr(&ptr)?;
         }
 
-        Ok(new_env)
+        Ok(false)
     }
 }
 
@@ -363,15 +1183,11 @@ impl EvalHelper {
         Ok((exp.tag == DataType::Cons) && (vm.get_car(&exp.clone())? == special))
     }
 
+    // Any cons which is not a special form is an application. Its operator
+    // is evaluated like any other expression, and checked when applied.
     #[inline]
-    fn functionp(ptr: TypedPointer) -> bool {
-        (ptr.tag == DataType::BuiltInFunction) || (ptr.tag == DataType::Function)
-    }
-
-    #[inline]
-    fn applicationp(vm: &VirtualMachine, ptr: TypedPointer) -> LispResult<bool> {
-        // TODO: check if car is function OR special form
-        Ok((ptr.tag == DataType::Cons) && (Self::functionp(vm.lookup(vm.get_car(&ptr.clone())?)?)))
+    fn applicationp(ptr: TypedPointer) -> bool {
+        ptr.tag == DataType::Cons
     }
 
     #[inline]
@@ -389,11 +1205,28 @@ impl EvalHelper {
     // 	ptr.tag == DataType::BuiltInLiteral
     // }
 
+    #[inline]
+    fn environment_function_p(ptr: &TypedPointer) -> bool {
+        (*ptr == ConstSymbol::BIN_THE_ENVIRONMENT) || (*ptr == ConstSymbol::BIN_PRINT_ENV)
+    }
+
+    #[inline]
+    fn keeps_environment_p(ptr: &TypedPointer) -> bool {
+        EvalHelper::environment_function_p(ptr)
+            || (*ptr == ConstSymbol::BIN_APPLY)
+            || (*ptr == ConstSymbol::BIN_FUNCALL)
+    }
+
     #[inline]
     fn compound_function_p(ptr: TypedPointer) -> bool {
         ptr.tag == DataType::Function
     }
 
+    #[inline]
+    fn macro_p(ptr: &TypedPointer) -> bool {
+        ptr.tag == DataType::Macro
+    }
+
     /// Returns a tuple (lambda-list, body, environment)
     #[inline]
     fn get_fn_parts(
@@ -401,7 +1234,7 @@ impl EvalHelper {
         ptr: TypedPointer,
     ) -> LispResult<(TypedPointer, TypedPointer, TypedPointer)> {
         trace!("helper--get_fn_parts");
-        if ptr.tag != DataType::Function {
+        if (ptr.tag != DataType::Function) && (ptr.tag != DataType::Macro) {
             return Err(LispError::internal(
                 "Attempted to dismember non-function into function parts",
             ));
@@ -410,7 +1243,7 @@ impl EvalHelper {
         // Cast value of FUN to CONS since functions are stored on list area
         let fun = TypedPointer::new(DataType::Cons, ptr.value);
 
-        // ( (lambda-list . body) <env> )
+        // ( (lambda-list . body) <env> [name] )
         let env = vm.get_cadr(&fun)?;
         let fun = vm.get_car(&fun)?;
         let lambda_list = vm.get_car(&fun)?;
@@ -435,7 +1268,13 @@ impl EvalHelper {
 impl VirtualMachine {
     pub fn lookup(&self, atom: TypedPointer) -> LispResult<TypedPointer> {
         if atom.tag != DataType::Atom {
-            return Err(LispError::internal("attempted to lookup value of non-atom"));
+            return Err(self.type_error("symbol", &atom));
+        }
+
+        // Special variables are shallow-bound, so their current dynamic
+        // value always lives on the atom itself
+        if self.is_special(&atom) {
+            return self.lookup_global(atom);
         }
 
         let mut env = self.registers.env.clone();
@@ -456,6 +1295,18 @@ impl VirtualMachine {
             return Ok(value);
         }
 
-        self.lookup_atom_value(atom)
+        self.lookup_global(atom)
+    }
+
+    // Global (or current dynamic) value of a symbol, which must be bound.
+    fn lookup_global(&self, atom: TypedPointer) -> LispResult<TypedPointer> {
+        let value = self.lookup_atom_value(atom.clone())?;
+        if value.tag == DataType::Undefined {
+            return Err(LispError::unbound_variable(
+                self.atoms.area[atom.value].name.clone(),
+            ));
+        }
+
+        Ok(value)
     }
 }
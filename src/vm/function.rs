@@ -0,0 +1,97 @@
+use super::error::{LispError, LispResult};
+use super::{types::*, ConstSymbol};
+
+impl VirtualMachine {
+    /// Returns the lambda list, body and environment of a closure or macro.
+    pub fn closure_parts(
+        &self,
+        fun: &TypedPointer,
+    ) -> LispResult<(TypedPointer, TypedPointer, TypedPointer)> {
+        if !closure_p(fun) {
+            return Err(self.type_error("function", fun));
+        }
+
+        // ( (lambda-list . body) <env> [name] )
+        let closure = TypedPointer::new(DataType::Cons, fun.value);
+        let code = self.get_car(&closure)?;
+        let env = self.get_cadr(&closure)?;
+
+        Ok((self.get_car(&code)?, self.get_cdr(&code)?, env))
+    }
+
+    /// Symbol a closure or macro was first bound to, if any.
+    pub fn function_name(&self, fun: &TypedPointer) -> Option<TypedPointer> {
+        if !closure_p(fun) {
+            return None;
+        }
+
+        let closure = TypedPointer::new(DataType::Cons, fun.value);
+        let rest = self.get_cdr(&self.get_cdr(&closure).ok()?).ok()?;
+        if rest.tag != DataType::Cons {
+            return None;
+        }
+        self.get_car(&rest).ok()
+    }
+
+    /// Names a closure or macro after the symbol it is being bound to,
+    /// unless it already has a name. Other values are left untouched.
+    pub(crate) fn name_function(
+        &mut self,
+        fun: &TypedPointer,
+        symbol: &TypedPointer,
+    ) -> LispResult<()> {
+        if !closure_p(fun) || symbol.tag != DataType::Atom {
+            return Ok(());
+        }
+
+        let closure = TypedPointer::new(DataType::Cons, fun.value);
+        let env_cell = self.get_cdr(&closure)?;
+        if self.get_cdr(&env_cell)? != ConstSymbol::NIL {
+            return Ok(());
+        }
+
+        let name = self.make_cons()?;
+        self.set_car(&name, symbol.clone())?;
+        self.set_cdr(&name, ConstSymbol::NIL)?;
+        self.set_cdr(&env_cell, name)
+    }
+
+    /// Bindings captured by a closure, innermost first, up to the global
+    /// environment E0. Bindings shadowed by inner ones are left out.
+    pub fn captured_bindings(
+        &self,
+        fun: &TypedPointer,
+    ) -> LispResult<Vec<(TypedPointer, TypedPointer)>> {
+        let (_, _, mut env) = self.closure_parts(fun)?;
+        let mut bindings: Vec<(TypedPointer, TypedPointer)> = vec![];
+
+        while env.tag == DataType::Environment && env != ConstSymbol::E0 {
+            let mut frame: Vec<(TypedPointer, TypedPointer)> = self
+                .environments
+                .area
+                .get(env.value)
+                .ok_or_else(|| LispError::internal("closure over unallocated environment"))?
+                .data
+                .iter()
+                .filter(|(atom, _)| bindings.iter().all(|(bound, _)| bound != *atom))
+                .map(|(atom, value)| (atom.clone(), value.clone()))
+                .collect();
+
+            // Environments are unordered, so sort each frame by name
+            frame.sort_by(|(a, _), (b, _)| {
+                self.atoms.area[a.value]
+                    .name
+                    .cmp(&self.atoms.area[b.value].name)
+            });
+            bindings.extend(frame);
+            env = self.env_parent(env)?;
+        }
+
+        Ok(bindings)
+    }
+}
+
+// Closures and macros share the same layout on the list area
+fn closure_p(fun: &TypedPointer) -> bool {
+    (fun.tag == DataType::Function) || (fun.tag == DataType::Macro)
+}
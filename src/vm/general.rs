@@ -1,3 +1,4 @@
+use super::error::LispResult;
 use super::{types::*, ConstSymbol};
 
 impl VirtualMachine {
@@ -28,6 +29,24 @@ impl VirtualMachine {
             "complex",
             "vector",
             "setq",
+            "defvar",
+            "defparameter",
+            "catch-error",
+            "catch",
+            "unwind-protect",
+            "if",
+        ];
+
+        // Symbols with a fixed index, allocated after the primitive ones:
+        // tags used by the evaluator, and special variables
+        let reserved_atoms = vec![
+            "throw",
+            "continuation",
+            "*load-files*",
+            "*print-length*",
+            "*print-level*",
+            "*print-right-margin*",
+            "*print-circle*",
         ];
 
         let self_evaluating_atoms = vec!["nil", "t"];
@@ -37,7 +56,7 @@ impl VirtualMachine {
             let _ = vm.assign_value(atom.clone(), atom);
         };
 
-        for atom_name in primitive_atoms {
+        for atom_name in primitive_atoms.into_iter().chain(reserved_atoms) {
             let _ = vm.make_atom(atom_name);
         }
 
@@ -47,6 +66,27 @@ impl VirtualMachine {
 
         vm.make_default_env();
 
+        if let Some(paths) = std::env::var_os(super::load::MAJESTIC_PATH) {
+            vm.load_path = std::env::split_paths(&paths).collect();
+        }
+
+        // Files being loaded, innermost first
+        let _ = vm.declare_special(ConstSymbol::LOAD_FILES);
+        let _ = vm.assign_value(ConstSymbol::LOAD_FILES, ConstSymbol::NIL);
+
+        // Printer settings: no truncation, 80 columns for pretty printing,
+        // datum labels on circular structure only
+        let _ = vm.declare_special(ConstSymbol::PRINT_LENGTH);
+        let _ = vm.assign_value(ConstSymbol::PRINT_LENGTH, ConstSymbol::NIL);
+        let _ = vm.declare_special(ConstSymbol::PRINT_LEVEL);
+        let _ = vm.assign_value(ConstSymbol::PRINT_LEVEL, ConstSymbol::NIL);
+        let _ = vm.declare_special(ConstSymbol::PRINT_RIGHT_MARGIN);
+        if let Ok(width) = vm.make_number(Number::Integer(80)) {
+            let _ = vm.assign_value(ConstSymbol::PRINT_RIGHT_MARGIN, width);
+        }
+        let _ = vm.declare_special(ConstSymbol::PRINT_CIRCLE);
+        let _ = vm.assign_value(ConstSymbol::PRINT_CIRCLE, ConstSymbol::NIL);
+
         vm
     }
 
@@ -55,26 +95,31 @@ impl VirtualMachine {
             .make_environment(ConstSymbol::NIL)
             .expect("create E0 environment");
 
-        let primitives = vec![
-            ("cons", ConstSymbol::BIN_CONS),
-            ("list", ConstSymbol::BIN_LIST),
-            ("car", ConstSymbol::BIN_CAR),
-            ("cdr", ConstSymbol::BIN_CDR),
-            ("eval", ConstSymbol::BIN_EVAL),
-            ("eq", ConstSymbol::BIN_EQ),
-        ];
-
-        for (symbol, value) in primitives {
+        for (symbol, value) in super::primitive_eval::BUILTINS {
             let atom = self
                 .make_atom(symbol)
                 .expect("Create symbol for built-in function");
-            self.env_bind(e0.clone(), atom, value)
+            self.env_bind(e0.clone(), atom, value.clone())
                 .expect("Bind symbol to built-in function");
         }
     }
 
+    /// Binds the special variable `*argv*` to a list of strings holding
+    /// the command line arguments given to a script.
+    pub fn set_argv(&mut self, args: &[String]) -> LispResult<()> {
+        let atom = self.make_atom("*argv*")?;
+        let mut strings = vec![];
+        for arg in args {
+            strings.push(self.make_string(arg)?);
+        }
+        let list = self.make_list(&strings)?;
+
+        self.declare_special(atom.clone())?;
+        self.assign_value(atom, list)
+    }
+
     fn format_bytes(mut num: usize) -> String {
-        let units = vec!["bytes", "KB", "MB", "GB"];
+        let units = ["bytes", "KB", "MB", "GB"];
         let mut unit = 0;
 
         for i in 1..units.len() {
@@ -96,6 +141,9 @@ impl VirtualMachine {
 
         let used_atom_table = self.atoms.last * std::mem::size_of::<Atom>();
         let used_number_table = self.atoms.last * std::mem::size_of::<Number>();
+        let used_string_table: usize = (0..self.strings.last)
+            .map(|i| std::mem::size_of::<String>() + self.strings.area[i].len())
+            .sum();
         let used_list_area = self.lists.last * std::mem::size_of::<ListArea>();
         let used_stack_area = self.stack.last * std::mem::size_of::<StackArea>();
         let used_env_table: usize = (self.environments.last * std::mem::size_of::<Environment>())
@@ -107,13 +155,18 @@ impl VirtualMachine {
 
         let atom_table_size = ATOM_TABLE_SIZE * std::mem::size_of::<Atom>();
         let number_table_size = NUMBER_TABLE_SIZE * std::mem::size_of::<Number>();
+        let string_table_size = STRING_TABLE_SIZE * std::mem::size_of::<String>();
         let list_area_size = LIST_AREA_SIZE * std::mem::size_of::<Cons>();
         let stack_area_size = LISP_STACK_SIZE * std::mem::size_of::<TypedPointer>();
         let env_table_size = (ENV_TABLE_SIZE * std::mem::size_of::<Environment>())
             + (ENV_TABLE_SIZE * MAX_ENV_CAPACITY * std::mem::size_of::<TypedPointer>() * 2);
 
-        let total_size =
-            atom_table_size + number_table_size + list_area_size + stack_area_size + env_table_size;
+        let total_size = atom_table_size
+            + number_table_size
+            + string_table_size
+            + list_area_size
+            + stack_area_size
+            + env_table_size;
 
         println!("VM Statistics");
 
@@ -121,7 +174,7 @@ impl VirtualMachine {
         table.load_preset(UTF8_FULL_CONDENSED);
         table.apply_modifier(UTF8_ROUND_CORNERS);
         table.set_content_arrangement(ContentArrangement::Dynamic);
-        table.set_header(&vec!["Statistics", "Current", "Total", "Unit", "Contents"]);
+        table.set_header(vec!["Statistics", "Current", "Total", "Unit", "Contents"]);
 
         table.add_row(vec![
             "Atom Table",
@@ -139,6 +192,14 @@ impl VirtualMachine {
             &format!("{} numbers", self.numbers.last),
         ]);
 
+        table.add_row(vec![
+            "String Table",
+            &Self::format_bytes(used_string_table),
+            &Self::format_bytes(string_table_size),
+            &Self::format_bytes(std::mem::size_of::<String>()),
+            &format!("{} strings", self.strings.last),
+        ]);
+
         table.add_row(vec![
             "List Area",
             &Self::format_bytes(used_list_area),
@@ -168,6 +229,7 @@ impl VirtualMachine {
             &Self::format_bytes(
                 used_atom_table
                     + used_number_table
+                    + used_string_table
                     + used_list_area
                     + used_stack_area
                     + used_env_table,
@@ -189,7 +251,7 @@ impl VirtualMachine {
         table.load_preset(UTF8_BORDERS_ONLY);
         table.apply_modifier(UTF8_ROUND_CORNERS);
         table.set_content_arrangement(ContentArrangement::Dynamic);
-        table.set_header(&vec!["ADDR", "CAR", "CDR"]);
+        table.set_header(vec!["ADDR", "CAR", "CDR"]);
 
         for i in 0..self.lists.last {
             let cons = self.lists.area.get(i).unwrap();
@@ -212,7 +274,7 @@ impl VirtualMachine {
         table.load_preset(UTF8_BORDERS_ONLY);
         table.apply_modifier(UTF8_ROUND_CORNERS);
         table.set_content_arrangement(ContentArrangement::Dynamic);
-        table.set_header(&vec!["ADDR", "NAME", "VALUE"]);
+        table.set_header(vec!["ADDR", "NAME", "VALUE", "PLIST"]);
 
         for i in 0..self.atoms.last {
             let atom = self.atoms.area.get(i).unwrap();
@@ -220,6 +282,7 @@ impl VirtualMachine {
                 &format!("{:#08x}", i),
                 &atom.name,
                 &format!("{}", atom.value),
+                &crate::printer::format_object(self, &atom.plist),
             ]);
         }
 
@@ -235,7 +298,7 @@ impl VirtualMachine {
         table.load_preset(UTF8_BORDERS_ONLY);
         table.apply_modifier(UTF8_ROUND_CORNERS);
         table.set_content_arrangement(ContentArrangement::Dynamic);
-        table.set_header(&vec!["ADDR", "VALUE"]);
+        table.set_header(vec!["ADDR", "VALUE"]);
 
         for i in 0..self.numbers.last {
             let num = self.numbers.area.get(i).unwrap();
@@ -261,7 +324,7 @@ impl VirtualMachine {
         table.load_preset(UTF8_BORDERS_ONLY);
         table.apply_modifier(UTF8_ROUND_CORNERS);
         table.set_content_arrangement(ContentArrangement::Dynamic);
-        table.set_header(&vec!["NAME", "VALUE"]);
+        table.set_header(vec!["NAME", "VALUE"]);
 
         table.add_row(vec!["<PARENT>", &format!("{}", env.prev)]);
 
@@ -273,6 +336,56 @@ impl VirtualMachine {
         println!("{}", table);
     }
 
+    /// Prints every environment on the lexical chain starting at `env`,
+    /// innermost first.
+    pub fn print_lexical_env(&self, env: TypedPointer) {
+        let mut env = env;
+        while env.tag == DataType::Environment {
+            println!("Lexical environment E{}", env.value);
+            self.print_env(env.value);
+            env = self.environments.area[env.value].prev.clone();
+        }
+    }
+
+    pub fn print_dynamic_env(&self) {
+        use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+        use comfy_table::presets::UTF8_BORDERS_ONLY;
+        use comfy_table::*;
+
+        let mut table = Table::new();
+        table.load_preset(UTF8_BORDERS_ONLY);
+        table.apply_modifier(UTF8_ROUND_CORNERS);
+        table.set_content_arrangement(ContentArrangement::Dynamic);
+        table.set_header(vec!["NAME", "VALUE", "SHADOWED"]);
+
+        for i in 0..self.atoms.last {
+            let atom = self.atoms.area.get(i).unwrap();
+            if atom.special {
+                table.add_row(vec![
+                    &atom.name,
+                    &crate::printer::format_object(self, &atom.value),
+                    &crate::printer::format_object(self, &atom.bindlist),
+                ]);
+            }
+        }
+
+        println!("Dynamic environment");
+        println!("{}", table);
+    }
+
+    pub fn print_backtrace(&self, frames: &[Frame]) {
+        eprintln!("Backtrace:");
+        for (i, frame) in frames.iter().enumerate() {
+            let name = self.frame_name(frame).unwrap_or_default();
+            eprintln!(
+                "{:>4}: {} in {}",
+                i,
+                crate::printer::format_object(self, &frame.form),
+                crate::printer::format_object(self, &name),
+            );
+        }
+    }
+
     pub fn fmt_registers(&self) -> String {
         use comfy_table::modifiers::UTF8_ROUND_CORNERS;
         use comfy_table::presets::UTF8_BORDERS_ONLY;
@@ -282,7 +395,7 @@ impl VirtualMachine {
         table.load_preset(UTF8_BORDERS_ONLY);
         table.apply_modifier(UTF8_ROUND_CORNERS);
         table.set_content_arrangement(ContentArrangement::Dynamic);
-        table.set_header(&vec!["NAME", "VALUE"]);
+        table.set_header(vec!["NAME", "VALUE"]);
 
         table.add_row(vec!["exp", &format!("{}", self.registers.exp)]);
         table.add_row(vec!["env", &format!("{}", self.registers.env)]);
@@ -1,5 +1,6 @@
 use super::error::{LispError, LispResult};
 use super::types::*;
+use super::ConstSymbol;
 
 impl VirtualMachine {
     pub fn make_cons(&mut self) -> LispResult<TypedPointer> {
@@ -14,7 +15,7 @@ impl VirtualMachine {
 
     fn get_cons(&self, cons: &TypedPointer) -> LispResult<&Cons> {
         if cons.tag != DataType::Cons {
-            return Err(LispError::internal("attempted to get CAR/CDR of non-cons"));
+            return Err(self.type_error("cons", cons));
         }
 
         Ok(self.lists.area.get(cons.value).unwrap())
@@ -22,7 +23,7 @@ impl VirtualMachine {
 
     fn get_cons_mut(&mut self, cons: &TypedPointer) -> LispResult<&mut Cons> {
         if cons.tag != DataType::Cons {
-            return Err(LispError::internal("attempted to set CAR/CDR of non-cons"));
+            return Err(self.type_error("cons", cons));
         }
 
         Ok(self.lists.area.get_mut(cons.value).unwrap())
@@ -57,4 +58,25 @@ impl VirtualMachine {
     pub fn get_caddr(&self, cons: &TypedPointer) -> LispResult<TypedPointer> {
         self.get_car(&self.get_cdr(&self.get_cdr(cons)?)?)
     }
+
+    pub fn make_list(&mut self, items: &[TypedPointer]) -> LispResult<TypedPointer> {
+        let mut list = ConstSymbol::NIL;
+        for item in items.iter().rev() {
+            let cons = self.make_cons()?;
+            self.set_car(&cons, item.clone())?;
+            self.set_cdr(&cons, list)?;
+            list = cons;
+        }
+        Ok(list)
+    }
+
+    pub fn list_to_vec(&self, list: TypedPointer) -> LispResult<Vec<TypedPointer>> {
+        let mut v = vec![];
+        let mut iter = list;
+        while iter != ConstSymbol::NIL {
+            v.push(self.get_car(&iter)?);
+            iter = self.get_cdr(&iter)?;
+        }
+        Ok(v)
+    }
 }
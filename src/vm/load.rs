@@ -0,0 +1,100 @@
+use super::error::{LispError, LispResult};
+use super::{types::*, ConstSymbol};
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding directories searched by `load`, used as
+/// the initial load path.
+pub const MAJESTIC_PATH: &str = "MAJESTIC_PATH";
+
+impl VirtualMachine {
+    /// Files being loaded, innermost first.
+    pub fn loading_files(&self) -> LispResult<Vec<String>> {
+        let files = self.lookup_atom_value(ConstSymbol::LOAD_FILES)?;
+        Ok(self
+            .list_to_vec(files)?
+            .iter()
+            .map(|file| self.strings.area[file.value].clone())
+            .collect())
+    }
+
+    /// Resolves the file name given to `load`. Relative names are searched
+    /// on the directory of the file being loaded (or the current directory
+    /// at top level), then on each directory of the load path. A name
+    /// without extension may also refer to a `.maj` file.
+    pub fn resolve_load_path(&self, name: &str) -> LispResult<PathBuf> {
+        let path = Path::new(name);
+
+        let mut dirs = vec![];
+        if path.is_relative() {
+            let current = self.loading_files()?.first().map(PathBuf::from);
+            dirs.push(match current.as_ref().and_then(|file| file.parent()) {
+                Some(dir) => dir.to_path_buf(),
+                None => PathBuf::from("."),
+            });
+
+            dirs.extend(self.load_path.iter().cloned());
+        } else {
+            dirs.push(PathBuf::new());
+        }
+
+        let mut candidates = vec![path.to_path_buf()];
+        if path.extension().is_none() {
+            candidates.push(path.with_extension("maj"));
+        }
+
+        dirs.iter()
+            .flat_map(|dir| candidates.iter().map(move |file| dir.join(file)))
+            .find(|file| file.is_file())
+            .and_then(|file| file.canonicalize().ok())
+            .ok_or_else(|| LispError::load(format!("could not find file {}", name)))
+    }
+
+    /// Loads a file as `(load name)` would, returning the value of its last
+    /// form. Quoting the built-in keeps this independent of the current
+    /// binding of `load`.
+    pub fn load_file(&mut self, name: &str) -> LispResult<TypedPointer> {
+        let quoted = self.make_list(&[ConstSymbol::QUOTE, ConstSymbol::BIN_LOAD])?;
+        let name = self.make_string(name)?;
+        let form = self.make_list(&[quoted, name])?;
+        self.evaluate(form)
+    }
+
+    /// Reads and parses a source file, returning its forms wrapped on a
+    /// `do` form, so that they are evaluated in order.
+    pub fn read_source_file(&mut self, path: &Path) -> LispResult<TypedPointer> {
+        let name = path.display().to_string();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| LispError::load(format!("could not read {}: {}", name, e)))?;
+
+        self.read_source(&name, &text)
+    }
+
+    /// Parses source text registered under `name`, returning its forms
+    /// wrapped on a `do` form.
+    pub fn read_source(&mut self, name: &str, text: &str) -> LispResult<TypedPointer> {
+        use crate::parser::combinators::Combinators;
+        use chumsky::Parser;
+
+        // Loading happens while the forms of the loading file are still
+        // being read, so its file is current again once this one is read
+        let previous = self.sources.current;
+        let file = self.add_source(name, text);
+        let expressions = match Combinators::parser().parse(text) {
+            Ok(expressions) => expressions,
+            Err(errors) => {
+                self.sources.current = previous;
+                return Err(LispError::syntax(file, name.to_owned(), errors));
+            }
+        };
+
+        let forms: LispResult<Vec<TypedPointer>> = expressions
+            .into_iter()
+            .map(|expr| crate::parser::convert::build_ast(self, expr))
+            .collect();
+        self.sources.current = previous;
+
+        let mut forms = forms?;
+        forms.insert(0, ConstSymbol::DO);
+        self.make_list(&forms)
+    }
+}
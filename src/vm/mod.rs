@@ -1,24 +1,27 @@
 pub mod atoms;
+pub mod backtrace;
+pub mod condition;
 pub mod constants;
+pub mod continuation;
+pub mod dynamic;
 pub mod environment;
 pub mod error;
 pub mod evaluate;
+pub mod function;
 pub mod general;
 pub mod lists;
+pub mod load;
+pub mod prelude;
 pub mod primitive_eval;
+pub mod source;
 pub mod stack;
 pub mod types;
 
-pub use atoms::*;
 pub use constants::*;
-pub use general::*;
 pub use types::*;
 
 // Maybe not needed?
-pub use environment::*;
-pub use evaluate::*;
 pub use primitive_eval::*;
-pub use stack::*;
 
 #[cfg(test)]
 mod test;
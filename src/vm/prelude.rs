@@ -0,0 +1,21 @@
+use super::error::LispResult;
+use super::types::*;
+
+/// Source of the prelude, compiled into the crate.
+pub static PRELUDE: &str = include_str!("prelude.maj");
+
+impl VirtualMachine {
+    /// Creates a virtual machine and evaluates the prelude on it.
+    pub fn with_prelude() -> LispResult<Box<Self>> {
+        let mut vm = Self::new();
+        vm.load_prelude()?;
+        Ok(vm)
+    }
+
+    /// Evaluates the prelude on the global environment.
+    pub fn load_prelude(&mut self) -> LispResult<()> {
+        let forms = self.read_source("<prelude>", PRELUDE)?;
+        self.evaluate(forms)?;
+        Ok(())
+    }
+}
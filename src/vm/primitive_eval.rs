@@ -1,4 +1,4 @@
-use super::error::{LispError, LispResult};
+use super::error::{ArgCount, LispError, LispResult};
 use super::{types::*, ConstSymbol};
 use log::{debug, trace, warn};
 
@@ -15,17 +15,159 @@ impl VirtualMachine {
             ConstSymbol::BIN_CAR => builtin_car(self, argl),
             ConstSymbol::BIN_CDR => builtin_cdr(self, argl),
             ConstSymbol::BIN_LIST => builtin_list(self, argl),
-            ConstSymbol::BIN_EVAL => builtin_eval(self, argl),
             ConstSymbol::BIN_EQ => builtin_eq(self, argl),
+            ConstSymbol::BIN_GET => builtin_get(self, argl),
+            ConstSymbol::BIN_PUT => builtin_put(self, argl),
+            ConstSymbol::BIN_REMPROP => builtin_remprop(self, argl),
+            ConstSymbol::BIN_SYMBOL_PLIST => builtin_symbol_plist(self, argl),
+            ConstSymbol::BIN_ERR => builtin_err(self, argl),
+            ConstSymbol::BIN_ERRORP => builtin_errorp(self, argl),
+            ConstSymbol::BIN_ERROR_KIND => builtin_error_kind(self, argl),
+            ConstSymbol::BIN_ERROR_MESSAGE => builtin_error_message(self, argl),
+            ConstSymbol::BIN_ERROR_IRRITANTS => builtin_error_irritants(self, argl),
+            ConstSymbol::BIN_THROW => builtin_throw(self, argl),
+            ConstSymbol::BIN_BACKTRACE => builtin_backtrace(self, argl),
+            ConstSymbol::BIN_PPRINT => builtin_pprint(self, argl),
+            ConstSymbol::BIN_DESCRIBE => builtin_describe(self, argl),
+            ConstSymbol::BIN_APPEND => builtin_append(self, argl),
+            ConstSymbol::BIN_EQUAL => builtin_equal(self, argl),
+            ConstSymbol::BIN_LENGTH => builtin_length(self, argl),
+            // Applied directly by the evaluator
+            ConstSymbol::BIN_EVAL
+            | ConstSymbol::BIN_CALL_CC
+            | ConstSymbol::BIN_APPLY
+            | ConstSymbol::BIN_FUNCALL
+            | ConstSymbol::BIN_LOAD
+            | ConstSymbol::BIN_THE_ENVIRONMENT
+            | ConstSymbol::BIN_PRINT_ENV => Err(LispError::internal(
+                "control primitive must be applied by the evaluator",
+            )),
             _ => Err(LispError::internal("unknown primitive function")),
         }
     }
+
+    /// Compares two values as `eq` does: numbers by value, and anything
+    /// else by identity.
+    pub fn values_eq(&self, first: &TypedPointer, second: &TypedPointer) -> LispResult<bool> {
+        if first.tag != second.tag {
+            return Ok(false);
+        }
+
+        Ok(match first.tag {
+            // Most values can be pointer-compared
+            DataType::Atom
+            | DataType::Cons
+            | DataType::BuiltInFunction
+            | DataType::BuiltInLiteral
+            | DataType::Function
+            | DataType::Macro
+            | DataType::Literal
+            | DataType::String => first.value == second.value,
+            // Environment comparison is undefined, so we better not compare at all
+            DataType::Environment => {
+                return Err(LispError::internal(
+                    "attempted to eq-compare two environments",
+                ))
+            }
+            // Numbers should be compared by actual value
+            DataType::Number => {
+                warn!("eq-comparing numbers; this should be improved");
+                self.numbers.area[first.value] == self.numbers.area[second.value]
+            }
+            // "undefined == undefined" could be seen as true, but this
+            // does not make sense at all
+            DataType::Undefined => {
+                return Err(LispError::internal(
+                    "attempted to compare two undefined values",
+                ))
+            }
+        })
+    }
+}
+
+/// Built-in functions, by the name they are bound to on E0.
+pub const BUILTINS: &[(&str, TypedPointer)] = &[
+    ("cons", ConstSymbol::BIN_CONS),
+    ("list", ConstSymbol::BIN_LIST),
+    ("car", ConstSymbol::BIN_CAR),
+    ("cdr", ConstSymbol::BIN_CDR),
+    ("eval", ConstSymbol::BIN_EVAL),
+    ("eq", ConstSymbol::BIN_EQ),
+    ("get", ConstSymbol::BIN_GET),
+    ("put", ConstSymbol::BIN_PUT),
+    ("remprop", ConstSymbol::BIN_REMPROP),
+    ("symbol-plist", ConstSymbol::BIN_SYMBOL_PLIST),
+    ("print-env", ConstSymbol::BIN_PRINT_ENV),
+    ("err", ConstSymbol::BIN_ERR),
+    ("errorp", ConstSymbol::BIN_ERRORP),
+    ("error-kind", ConstSymbol::BIN_ERROR_KIND),
+    ("error-message", ConstSymbol::BIN_ERROR_MESSAGE),
+    ("error-irritants", ConstSymbol::BIN_ERROR_IRRITANTS),
+    ("throw", ConstSymbol::BIN_THROW),
+    ("call/cc", ConstSymbol::BIN_CALL_CC),
+    ("apply", ConstSymbol::BIN_APPLY),
+    ("funcall", ConstSymbol::BIN_FUNCALL),
+    ("the-environment", ConstSymbol::BIN_THE_ENVIRONMENT),
+    ("backtrace", ConstSymbol::BIN_BACKTRACE),
+    ("load", ConstSymbol::BIN_LOAD),
+    ("pprint", ConstSymbol::BIN_PPRINT),
+    ("describe", ConstSymbol::BIN_DESCRIBE),
+    ("append", ConstSymbol::BIN_APPEND),
+    ("equal", ConstSymbol::BIN_EQUAL),
+    ("length", ConstSymbol::BIN_LENGTH),
+];
+
+/// Name of a built-in function, or `None` if the pointer is not one.
+pub fn builtin_name(fun: &TypedPointer) -> Option<&'static str> {
+    BUILTINS
+        .iter()
+        .find(|(_, builtin)| builtin == fun)
+        .map(|(name, _)| *name)
+}
+
+/// Number of arguments accepted by a built-in function, or `None` if the
+/// pointer is not a built-in function.
+pub fn builtin_arity(fun: &TypedPointer) -> Option<ArgCount> {
+    let arity = match *fun {
+        ConstSymbol::BIN_LIST | ConstSymbol::BIN_APPEND => ArgCount::AtLeast(0),
+        ConstSymbol::BIN_THE_ENVIRONMENT | ConstSymbol::BIN_BACKTRACE => ArgCount::Exactly(0),
+        ConstSymbol::BIN_CAR
+        | ConstSymbol::BIN_CDR
+        | ConstSymbol::BIN_SYMBOL_PLIST
+        | ConstSymbol::BIN_ERRORP
+        | ConstSymbol::BIN_ERROR_KIND
+        | ConstSymbol::BIN_ERROR_MESSAGE
+        | ConstSymbol::BIN_ERROR_IRRITANTS
+        | ConstSymbol::BIN_CALL_CC
+        | ConstSymbol::BIN_LOAD
+        | ConstSymbol::BIN_PPRINT
+        | ConstSymbol::BIN_DESCRIBE
+        | ConstSymbol::BIN_LENGTH => ArgCount::Exactly(1),
+        ConstSymbol::BIN_CONS
+        | ConstSymbol::BIN_EQ
+        | ConstSymbol::BIN_EQUAL
+        | ConstSymbol::BIN_GET
+        | ConstSymbol::BIN_REMPROP
+        | ConstSymbol::BIN_THROW => ArgCount::Exactly(2),
+        ConstSymbol::BIN_PUT => ArgCount::Exactly(3),
+        ConstSymbol::BIN_PRINT_ENV => ArgCount::Between(0, 1),
+        ConstSymbol::BIN_EVAL => ArgCount::Between(1, 2),
+        ConstSymbol::BIN_ERR | ConstSymbol::BIN_FUNCALL => ArgCount::AtLeast(1),
+        ConstSymbol::BIN_APPLY => ArgCount::AtLeast(2),
+        _ => return None,
+    };
+
+    Some(arity)
 }
 
 fn builtin_cons(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
     trace!("builtin_cons");
     if argl.len() != 2 {
-        return Err(LispError::arity("cons".to_owned()));
+        return Err(LispError::wrong_arg_count(
+            "cons".to_owned(),
+            ArgCount::Exactly(2),
+            argl.len(),
+        ));
     }
 
     let car = argl[0].clone();
@@ -41,7 +183,11 @@ fn builtin_cons(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<Ty
 fn builtin_car(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
     trace!("builtin_car");
     if argl.len() != 1 {
-        return Err(LispError::arity("car".to_owned()));
+        return Err(LispError::wrong_arg_count(
+            "car".to_owned(),
+            ArgCount::Exactly(1),
+            argl.len(),
+        ));
     }
 
     let value = argl[0].clone();
@@ -56,7 +202,11 @@ fn builtin_car(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<Typ
 fn builtin_cdr(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
     trace!("builtin_cdr");
     if argl.len() != 1 {
-        return Err(LispError::arity("car".to_owned()));
+        return Err(LispError::wrong_arg_count(
+            "cdr".to_owned(),
+            ArgCount::Exactly(1),
+            argl.len(),
+        ));
     }
 
     let value = argl[0].clone();
@@ -81,76 +231,374 @@ fn builtin_list(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<Ty
     Ok(iter)
 }
 
-fn builtin_eval(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
-    trace!("builtin_eval");
+fn builtin_append(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_append");
+    // Every list but the last is copied, and the last one is shared
+    let mut iter = argl.last().cloned().unwrap_or(ConstSymbol::NIL);
+    for list in argl.iter().rev().skip(1) {
+        if (*list != ConstSymbol::NIL) && (list.tag != DataType::Cons) {
+            return Err(vm.type_error("list", list));
+        }
+
+        for value in vm.list_to_vec(list.clone())?.into_iter().rev() {
+            let cons = vm.make_cons()?;
+            vm.set_car(&cons, value)?;
+            vm.set_cdr(&cons, iter)?;
+            iter = cons;
+        }
+    }
+
+    Ok(iter)
+}
+
+fn builtin_length(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_length");
     if argl.len() != 1 {
-        return Err(LispError::arity("eval".to_owned()));
+        return Err(LispError::wrong_arg_count(
+            "length".to_owned(),
+            ArgCount::Exactly(1),
+            argl.len(),
+        ));
     }
 
-    // Push stack frame
-    vm.stack_push(vm.registers.argl.clone())?;
-    vm.stack_push(vm.registers.cont.clone())?;
-    vm.stack_push(vm.registers.env.clone())?;
-    vm.stack_push(vm.registers.exp.clone())?;
-    vm.stack_push(vm.registers.fun.clone())?;
-    vm.stack_push(vm.registers.unev.clone())?;
+    // Only proper lists have a length
+    let mut length = 0;
+    let mut iter = argl[0].clone();
+    while iter.tag == DataType::Cons {
+        length += 1;
+        iter = vm.get_cdr(&iter)?;
+    }
 
-    // Evaluate
-    let val = vm.evaluate(argl[0].clone())?;
+    if iter != ConstSymbol::NIL {
+        return Err(vm.type_error("list", &argl[0]));
+    }
+
+    vm.make_number(Number::Integer(length))
+}
 
-    // Pop stack frame
-    vm.registers.unev = vm.stack_pop()?;
-    vm.registers.fun = vm.stack_pop()?;
-    vm.registers.exp = vm.stack_pop()?;
-    vm.registers.env = vm.stack_pop()?;
-    vm.registers.cont = vm.stack_pop()?;
-    vm.registers.argl = vm.stack_pop()?;
+/// Environment of the application, given by the evaluator.
+pub(super) fn builtin_the_environment(
+    env: TypedPointer,
+    argl: &[TypedPointer],
+) -> LispResult<TypedPointer> {
+    trace!("builtin_the_environment");
+    if !argl.is_empty() {
+        return Err(LispError::wrong_arg_count(
+            "the-environment".to_owned(),
+            ArgCount::Exactly(0),
+            argl.len(),
+        ));
+    }
 
-    Ok(val)
+    Ok(env)
 }
 
 fn builtin_eq(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
     trace!("builtin_eq");
     if argl.len() != 2 {
-        return Err(LispError::arity("eq".to_owned()));
+        return Err(LispError::wrong_arg_count(
+            "eq".to_owned(),
+            ArgCount::Exactly(2),
+            argl.len(),
+        ));
     }
 
-    let first = argl[0].clone();
-    let second = argl[1].clone();
+    Ok(if vm.values_eq(&argl[0], &argl[1])? {
+        ConstSymbol::T
+    } else {
+        ConstSymbol::NIL
+    })
+}
 
-    let convert = |v| if v { ConstSymbol::T } else { ConstSymbol::NIL };
+fn builtin_equal(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_equal");
+    if argl.len() != 2 {
+        return Err(LispError::wrong_arg_count(
+            "equal".to_owned(),
+            ArgCount::Exactly(2),
+            argl.len(),
+        ));
+    }
 
-    Ok(if first.tag != second.tag {
+    Ok(if values_equal(vm, &argl[0], &argl[1])? {
+        ConstSymbol::T
+    } else {
         ConstSymbol::NIL
+    })
+}
+
+// Conses are equal when their elements are, numbers and strings when their
+// values are, and anything else when it is the same object. Lists are
+// walked along their cdrs, so only nesting recurses.
+fn values_equal(
+    vm: &VirtualMachine,
+    first: &TypedPointer,
+    second: &TypedPointer,
+) -> LispResult<bool> {
+    let mut first = first.clone();
+    let mut second = second.clone();
+    while (first.tag == DataType::Cons) && (second.tag == DataType::Cons) {
+        if !values_equal(vm, &vm.get_car(&first)?, &vm.get_car(&second)?)? {
+            return Ok(false);
+        }
+        first = vm.get_cdr(&first)?;
+        second = vm.get_cdr(&second)?;
+    }
+
+    Ok((first.tag == second.tag)
+        && match first.tag {
+            DataType::Number => vm.numbers.area[first.value] == vm.numbers.area[second.value],
+            DataType::String => vm.strings.area[first.value] == vm.strings.area[second.value],
+            _ => first.value == second.value,
+        })
+}
+
+fn builtin_get(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_get");
+    if argl.len() != 2 {
+        return Err(LispError::wrong_arg_count(
+            "get".to_owned(),
+            ArgCount::Exactly(2),
+            argl.len(),
+        ));
+    }
+
+    vm.get_property(argl[0].clone(), argl[1].clone())
+}
+
+fn builtin_put(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_put");
+    if argl.len() != 3 {
+        return Err(LispError::wrong_arg_count(
+            "put".to_owned(),
+            ArgCount::Exactly(3),
+            argl.len(),
+        ));
+    }
+
+    let value = argl[2].clone();
+    vm.put_property(argl[0].clone(), argl[1].clone(), value.clone())?;
+
+    Ok(value)
+}
+
+fn builtin_remprop(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_remprop");
+    if argl.len() != 2 {
+        return Err(LispError::wrong_arg_count(
+            "remprop".to_owned(),
+            ArgCount::Exactly(2),
+            argl.len(),
+        ));
+    }
+
+    Ok(if vm.remove_property(argl[0].clone(), argl[1].clone())? {
+        ConstSymbol::T
     } else {
-        match first.tag {
-            // Most values can be pointer-compared
-            DataType::Atom
-            | DataType::Cons
-            | DataType::BuiltInFunction
-            | DataType::BuiltInLiteral
-            | DataType::Function
-            | DataType::Literal => convert(first.value == second.value),
-            // Environment comparison is undefined, so we better not compare at all
-            DataType::Environment => {
-                return Err(LispError::internal(
-                    "attempted to eq-compare two environments",
-                ))
-            }
-            // Numbers should be compared by actual value
-            DataType::Number => {
-                warn!("eq-comparing numbers; this should be improved");
-                let first: &Number = vm.numbers.area.get(first.value).unwrap();
-                let second: &Number = vm.numbers.area.get(second.value).unwrap();
-                convert(first == second)
-            }
-            // "undefined == undefined" could be seen as true, but this
-            // does not make sense at all
-            DataType::Undefined => {
-                return Err(LispError::internal(
-                    "attempted to compare two undefined values",
-                ))
-            }
+        ConstSymbol::NIL
+    })
+}
+
+fn builtin_symbol_plist(
+    vm: &mut VirtualMachine,
+    argl: &[TypedPointer],
+) -> LispResult<TypedPointer> {
+    trace!("builtin_symbol_plist");
+    if argl.len() != 1 {
+        return Err(LispError::wrong_arg_count(
+            "symbol-plist".to_owned(),
+            ArgCount::Exactly(1),
+            argl.len(),
+        ));
+    }
+
+    vm.symbol_plist(argl[0].clone())
+}
+
+/// Prints the lexical bindings of the environment of the application,
+/// given by the evaluator, and the dynamic bindings.
+pub(super) fn builtin_print_env(
+    vm: &mut VirtualMachine,
+    env: TypedPointer,
+    argl: &[TypedPointer],
+) -> LispResult<TypedPointer> {
+    trace!("builtin_print_env");
+    if argl.len() > 1 {
+        return Err(LispError::wrong_arg_count(
+            "print-env".to_owned(),
+            ArgCount::Between(0, 1),
+            argl.len(),
+        ));
+    }
+
+    let which = argl
+        .first()
+        .map(|ptr| match ptr.tag {
+            DataType::Atom => Ok((ptr, vm.atoms.area[ptr.value].name.as_str())),
+            _ => Err(vm.type_error("lexical or dynamic", ptr)),
+        })
+        .transpose()?;
+
+    match which {
+        None => {
+            vm.print_lexical_env(env);
+            vm.print_dynamic_env();
         }
+        Some((_, "lexical")) => vm.print_lexical_env(env),
+        Some((_, "dynamic")) => vm.print_dynamic_env(),
+        Some((ptr, _)) => return Err(vm.type_error("lexical or dynamic", ptr)),
+    }
+
+    Ok(ConstSymbol::NIL)
+}
+
+// (err [kind] "format {}" irritants...)
+fn builtin_err(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_err");
+    let (kind, argl) = match argl.first() {
+        Some(ptr) if ptr.tag == DataType::Atom => (ptr.clone(), &argl[1..]),
+        _ => (vm.make_atom("simple-error")?, argl),
+    };
+
+    if argl.is_empty() {
+        return Err(LispError::wrong_arg_count(
+            "err".to_owned(),
+            ArgCount::AtLeast(1),
+            argl.len(),
+        ));
+    }
+
+    if argl[0].tag != DataType::String {
+        return Err(vm.type_error("string", &argl[0]));
+    }
+
+    let irritants = builtin_list(vm, &argl[1..])?;
+    let message = crate::printer::format_string(vm, &vm.strings.area[argl[0].value], &argl[1..]);
+    let message_ptr = vm.make_string(&message)?;
+    let object = vm.make_error_object(kind, message_ptr, irritants)?;
+
+    Err(LispError::signal(object, message))
+}
+
+fn builtin_errorp(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_errorp");
+    if argl.len() != 1 {
+        return Err(LispError::wrong_arg_count(
+            "errorp".to_owned(),
+            ArgCount::Exactly(1),
+            argl.len(),
+        ));
+    }
+
+    Ok(if vm.is_error_object(&argl[0]) {
+        ConstSymbol::T
+    } else {
+        ConstSymbol::NIL
     })
 }
+
+fn builtin_error_kind(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_error_kind");
+    if argl.len() != 1 {
+        return Err(LispError::wrong_arg_count(
+            "error-kind".to_owned(),
+            ArgCount::Exactly(1),
+            argl.len(),
+        ));
+    }
+
+    Ok(vm.error_object_parts(&argl[0])?.0)
+}
+
+fn builtin_error_message(
+    vm: &mut VirtualMachine,
+    argl: &[TypedPointer],
+) -> LispResult<TypedPointer> {
+    trace!("builtin_error_message");
+    if argl.len() != 1 {
+        return Err(LispError::wrong_arg_count(
+            "error-message".to_owned(),
+            ArgCount::Exactly(1),
+            argl.len(),
+        ));
+    }
+
+    Ok(vm.error_object_parts(&argl[0])?.1)
+}
+
+fn builtin_error_irritants(
+    vm: &mut VirtualMachine,
+    argl: &[TypedPointer],
+) -> LispResult<TypedPointer> {
+    trace!("builtin_error_irritants");
+    if argl.len() != 1 {
+        return Err(LispError::wrong_arg_count(
+            "error-irritants".to_owned(),
+            ArgCount::Exactly(1),
+            argl.len(),
+        ));
+    }
+
+    Ok(vm.error_object_parts(&argl[0])?.2)
+}
+
+fn builtin_throw(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_throw");
+    if argl.len() != 2 {
+        return Err(LispError::wrong_arg_count(
+            "throw".to_owned(),
+            ArgCount::Exactly(2),
+            argl.len(),
+        ));
+    }
+
+    let tag_name = crate::printer::format_object(vm, &argl[0]);
+    Err(LispError::throw(argl[0].clone(), argl[1].clone(), tag_name))
+}
+
+// List of (name form) for each active application, innermost first. The
+// call to backtrace itself is the first entry.
+fn builtin_backtrace(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_backtrace");
+    if !argl.is_empty() {
+        return Err(LispError::wrong_arg_count(
+            "backtrace".to_owned(),
+            ArgCount::Exactly(0),
+            argl.len(),
+        ));
+    }
+
+    vm.backtrace_list()
+}
+
+// Prints an object laid out over multiple lines, returning nil.
+fn builtin_pprint(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_pprint");
+    if argl.len() != 1 {
+        return Err(LispError::wrong_arg_count(
+            "pprint".to_owned(),
+            ArgCount::Exactly(1),
+            argl.len(),
+        ));
+    }
+
+    crate::printer::print_pretty(vm, &argl[0]);
+    println!();
+    Ok(ConstSymbol::NIL)
+}
+
+// Prints the lambda list, body and captured bindings of a function,
+// returning nil.
+fn builtin_describe(vm: &mut VirtualMachine, argl: &[TypedPointer]) -> LispResult<TypedPointer> {
+    trace!("builtin_describe");
+    if argl.len() != 1 {
+        return Err(LispError::wrong_arg_count(
+            "describe".to_owned(),
+            ArgCount::Exactly(1),
+            argl.len(),
+        ));
+    }
+
+    print!("{}", crate::printer::format_description(vm, &argl[0])?);
+    Ok(ConstSymbol::NIL)
+}
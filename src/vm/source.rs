@@ -0,0 +1,79 @@
+use super::error::LispError;
+use super::types::*;
+
+impl VirtualMachine {
+    /// Registers a source file and makes it the current one, so that
+    /// expressions read next are located on it. Returns the file index.
+    pub fn add_source(&mut self, name: &str, text: &str) -> usize {
+        self.sources.files.push(SourceFile {
+            name: name.to_owned(),
+            text: text.to_owned(),
+        });
+        let file = self.sources.files.len() - 1;
+        self.sources.current = Some(file);
+        file
+    }
+
+    /// Makes the source file registered under `name` hold `text`, or
+    /// registers it. Locations on its previous text are forgotten, so that
+    /// sources read over and over do not pile up. Returns the file index.
+    pub fn replace_source(&mut self, name: &str, text: &str) -> usize {
+        let file = match self.sources.files.iter().position(|f| f.name == name) {
+            Some(file) => file,
+            None => return self.add_source(name, text),
+        };
+
+        self.sources.files[file].text = text.to_owned();
+        self.sources
+            .locations
+            .retain(|_, location| location.file != file);
+        self.sources.current = Some(file);
+        file
+    }
+
+    pub fn get_source(&self, file: usize) -> Option<&SourceFile> {
+        self.sources.files.get(file)
+    }
+
+    /// Records the span of a cons cell on the current source file.
+    pub fn set_location(&mut self, ptr: &TypedPointer, span: std::ops::Range<usize>) {
+        if ptr.tag != DataType::Cons {
+            return;
+        }
+
+        if let Some(file) = self.sources.current {
+            self.sources
+                .locations
+                .insert(ptr.value, SourceLocation { file, span });
+        }
+    }
+
+    /// Moves the location of an object read from source onto the object
+    /// which replaces it.
+    pub fn move_location(&mut self, from: &TypedPointer, to: &TypedPointer) {
+        if (from.tag != DataType::Cons) || (to.tag != DataType::Cons) {
+            return;
+        }
+
+        if let Some(location) = self.sources.locations.remove(&from.value) {
+            self.sources.locations.insert(to.value, location);
+        }
+    }
+
+    pub fn get_location(&self, ptr: &TypedPointer) -> Option<&SourceLocation> {
+        if ptr.tag != DataType::Cons {
+            return None;
+        }
+
+        self.sources.locations.get(&ptr.value)
+    }
+
+    /// Location of the innermost expression on the backtrace of an error
+    /// which was read from source.
+    pub fn error_location(&self, error: &LispError) -> Option<&SourceLocation> {
+        error
+            .backtrace()
+            .iter()
+            .find_map(|frame| self.get_location(&frame.form))
+    }
+}
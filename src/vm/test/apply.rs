@@ -0,0 +1,117 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::error::*;
+use crate::vm::types::VirtualMachine;
+
+/// Apply built-in and compound functions to spread arguments.
+#[test]
+fn apply() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(apply cons (quote (1 2)))")?;
+    assert_eq!(format_object(&vm, &value), "(1 . 2)");
+
+    let value = eval_str(&mut vm, "(apply list 1 2 (quote (3 4)))")?;
+    assert_eq!(format_object(&vm, &value), "(1 2 3 4)");
+
+    let value = eval_str(&mut vm, "(apply list nil)")?;
+    assert_eq!(format_object(&vm, &value), "nil");
+
+    let value = eval_str(
+        &mut vm,
+        "(setq swap (fn (a b) (cons b a)))
+         (apply swap 1 (quote (2)))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(2 . 1)");
+
+    let value = eval_str(&mut vm, "(apply apply list 1 (quote ((2 3))))")?;
+    assert_eq!(format_object(&vm, &value), "(1 2 3)");
+
+    Ok(())
+}
+
+/// Funcall applies a function without spreading its last argument.
+#[test]
+fn funcall() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(funcall list 1 (quote (2 3)))")?;
+    assert_eq!(format_object(&vm, &value), "(1 (2 3))");
+
+    let value = eval_str(&mut vm, "(funcall (fn () (quote ok)))")?;
+    assert_eq!(format_object(&vm, &value), "ok");
+
+    Ok(())
+}
+
+/// Applying with wrong arguments is an error.
+#[test]
+fn apply_errors() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    assert!(eval_str(&mut vm, "(apply list)").is_err());
+    assert!(eval_str(&mut vm, "(apply list 1 2)").is_err());
+    assert!(eval_str(&mut vm, "(funcall)").is_err());
+    assert_eq!(vm.stack.last, 0);
+
+    Ok(())
+}
+
+/// The applied function is in tail position, so recursing through apply
+/// and funcall doesn't grow the stack. The backtrace, which holds one frame
+/// per pending application, shows it.
+#[test]
+fn apply_tail_position() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    // Walks a list by dispatching on whether it is empty
+    eval_str(
+        &mut vm,
+        "(setq walk (fn (xs) (funcall (get (quote next) (eq xs nil)) xs)))
+         (put (quote next) nil (fn (xs) (apply walk (list (cdr xs)))))
+         (put (quote next) t (fn (xs) (backtrace)))",
+    )?;
+
+    let numbers: Vec<String> = (0..300).map(|n| n.to_string()).collect();
+    let value = eval_str(&mut vm, &format!("(walk (quote ({})))", numbers.join(" ")))?;
+    assert_eq!(vm.list_to_vec(value)?.len(), 2);
+
+    // Out of tail position, each level stays on the backtrace
+    let value = eval_str(
+        &mut vm,
+        "(put (quote next) nil (fn (xs) (list (apply walk (list (cdr xs))))))
+         (car (car (car (walk (quote (1 2 3))))))",
+    )?;
+    assert!(vm.list_to_vec(value)?.len() > 6);
+
+    Ok(())
+}
+
+/// The operator of an application is evaluated as any other expression.
+#[test]
+fn operator_position() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "((fn (x) (cons x x)) 1)")?;
+    assert_eq!(format_object(&vm, &value), "(1 . 1)");
+
+    let value = eval_str(
+        &mut vm,
+        "(setq compose (fn (f g) (fn (x) (f (g x)))))
+         ((compose car cdr) (quote (1 2 3)))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "2");
+
+    let value = eval_str(&mut vm, "(((fn (x) (fn (y) (cons x y))) 1) 2)")?;
+    assert_eq!(format_object(&vm, &value), "(1 . 2)");
+
+    let value = eval_str(&mut vm, "((car (list cdr)) (quote (1 2)))")?;
+    assert_eq!(format_object(&vm, &value), "(2)");
+
+    // Operators which do not evaluate to functions are typed errors
+    let error = eval_str(&mut vm, "((quote (a b)) 1)").unwrap_err();
+    assert!(error.is_not_a_function());
+    assert_eq!(error.offending_value(), Some("(a b)"));
+
+    Ok(())
+}
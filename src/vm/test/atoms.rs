@@ -1,3 +1,5 @@
+#![allow(clippy::useless_conversion)]
+
 use crate::vm::constants::ConstSymbol;
 use crate::vm::error::*;
 use crate::vm::types::{DataType, Number, VirtualMachine};
@@ -234,3 +236,74 @@ fn assign_to_illegal_atom() -> LispResult<()> {
 
     Ok(())
 }
+
+/// Complete names of bound symbols from a prefix.
+#[test]
+fn complete_symbol() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    assert_eq!(vm.complete_symbol("ca"), vec!["call/cc", "car"]);
+    assert!(vm.complete_symbol("").contains(&"cons".to_owned()));
+    assert!(vm.complete_symbol("zzz").is_empty());
+
+    // Interned but unbound symbols are not completed
+    let atom = vm.make_atom("cadence")?;
+    assert_eq!(vm.complete_symbol("cad"), Vec::<String>::new());
+
+    let value = vm.make_number(Number::Integer(1))?;
+    vm.assign_value(atom, value)?;
+    assert_eq!(vm.complete_symbol("cad"), vec!["cadence"]);
+
+    Ok(())
+}
+
+/// Describe the arguments of functions bound to symbols.
+#[test]
+fn function_signature() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    assert_eq!(vm.function_signature("car").as_deref(), Some("1 argument"));
+    assert_eq!(
+        vm.function_signature("cons").as_deref(),
+        Some("2 arguments")
+    );
+    assert_eq!(
+        vm.function_signature("eval").as_deref(),
+        Some("1 to 2 arguments")
+    );
+    assert_eq!(
+        vm.function_signature("list").as_deref(),
+        Some("any number of arguments")
+    );
+
+    super::eval_str(
+        &mut vm,
+        "(setq pair (fn (a b) (cons a b)))
+         (setq thunk (fn () 1))
+         (setq x 1)",
+    )?;
+    assert_eq!(vm.function_signature("pair").as_deref(), Some("(a b)"));
+    assert_eq!(vm.function_signature("thunk").as_deref(), Some("()"));
+    assert_eq!(vm.function_signature("x"), None);
+    assert_eq!(vm.function_signature("no-such-symbol"), None);
+
+    Ok(())
+}
+
+/// Create atoms to the limits of the table, then expect an allocation
+/// error on the next new one. Existing atoms are still found.
+#[test]
+fn atom_table_full() -> LispResult<()> {
+    use crate::vm::ATOM_TABLE_SIZE;
+
+    let mut vm = VirtualMachine::new();
+
+    while vm.atoms.last < ATOM_TABLE_SIZE {
+        vm.make_atom(&format!("atom-{}", vm.atoms.last))?;
+    }
+
+    let err = vm.make_atom("one-too-many").unwrap_err();
+    assert_eq!(err.message(), "atom table allocation error");
+    assert_eq!(vm.make_atom("nil")?, ConstSymbol::NIL);
+    Ok(())
+}
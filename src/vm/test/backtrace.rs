@@ -0,0 +1,82 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::error::*;
+use crate::vm::types::VirtualMachine;
+
+/// Errors carry the application frames active when they were raised.
+#[test]
+fn error_backtrace() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    eval_str(
+        &mut vm,
+        "(setq f (fn (x) (list x (car x 2))))
+         (setq g (fn (y) (list 1 (f y))))",
+    )?;
+
+    let error = eval_str(&mut vm, "(g 5)").unwrap_err();
+    let forms: Vec<String> = error
+        .backtrace()
+        .iter()
+        .map(|frame| format_object(&vm, &frame.form))
+        .collect();
+    assert_eq!(
+        forms,
+        vec![
+            "(car x 2)",
+            "(list x (car x 2))",
+            "(f y)",
+            "(list 1 (f y))",
+            "(g 5)"
+        ]
+    );
+
+    let names: Vec<String> = error
+        .backtrace()
+        .iter()
+        .map(|frame| format_object(&vm, &vm.frame_name(frame).unwrap()))
+        .collect();
+    assert_eq!(names, vec!["car", "list", "f", "list", "g"]);
+
+    // Frames of the failed evaluation are dropped on recovery
+    assert!(vm.frames.is_empty());
+
+    Ok(())
+}
+
+/// Applications which returned do not show up on backtraces.
+#[test]
+fn backtrace_builtin() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(list (car (quote (1))) (backtrace))")?;
+    assert_eq!(
+        format_object(&vm, &value),
+        "(1 ((backtrace (backtrace)) (list (list (car (quote (1))) (backtrace)))))"
+    );
+
+    Ok(())
+}
+
+/// Tail calls only keep the latest calling function on each depth.
+#[test]
+fn backtrace_tail_calls() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    eval_str(
+        &mut vm,
+        "(setq a (fn () (b)))
+         (setq b (fn () (c)))
+         (setq c (fn () (car 1 2)))",
+    )?;
+
+    let error = eval_str(&mut vm, "(a)").unwrap_err();
+    let forms: Vec<String> = error
+        .backtrace()
+        .iter()
+        .map(|frame| format_object(&vm, &frame.form))
+        .collect();
+    assert_eq!(forms, vec!["(car 1 2)", "(c)"]);
+
+    Ok(())
+}
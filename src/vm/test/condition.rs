@@ -0,0 +1,106 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::error::*;
+use crate::vm::types::VirtualMachine;
+
+/// `err` signals an error carrying a formatted message, a kind and the
+/// list of irritants.
+#[test]
+fn signal_error() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let error = eval_str(&mut vm, "(err \"value {} is bad\" (quote foo))").unwrap_err();
+    assert_eq!(error.message(), "value foo is bad");
+
+    let object = error.object().expect("Lisp error object");
+    assert_eq!(
+        format_object(&vm, &object),
+        "#<ERROR simple-error: value foo is bad>"
+    );
+
+    let (kind, message, irritants) = vm.error_object_parts(&object)?;
+    assert_eq!(format_object(&vm, &kind), "simple-error");
+    assert_eq!(format_object(&vm, &message), "\"value foo is bad\"");
+    assert_eq!(format_object(&vm, &irritants), "(foo)");
+
+    Ok(())
+}
+
+/// `catch-error` applies its handler to the error object, and evaluates to
+/// the value of its form when nothing is signalled.
+#[test]
+fn catch_error() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(catch-error (quote ok) error-kind)")?;
+    assert_eq!(format_object(&vm, &value), "ok");
+
+    let value = eval_str(
+        &mut vm,
+        "(catch-error (err (quote my-error) \"oops {} {}\" 1 (quote x)) error-kind)",
+    )?;
+    assert_eq!(format_object(&vm, &value), "my-error");
+
+    let value = eval_str(
+        &mut vm,
+        "(catch-error (cons 1 (err \"oops {} {}\" 1 (quote x))) error-irritants)",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(1 x)");
+
+    // Stack must be left as it was found
+    assert_eq!(vm.stack.last, 0);
+
+    Ok(())
+}
+
+/// Errors raised by the VM itself are converted into error objects.
+#[test]
+fn catch_internal_error() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(catch-error (car (quote x)) error-kind)")?;
+    assert_eq!(format_object(&vm, &value), "type-error");
+
+    let value = eval_str(&mut vm, "(catch-error (car 1 2) error-message)")?;
+    assert_eq!(
+        format_object(&vm, &value),
+        "\"wrong number of arguments to car: expected 1, got 2\""
+    );
+
+    // Accessors of error objects and print-env check their arguments
+    for text in [
+        "(error-kind 1)",
+        "(error-message (quote x))",
+        "(error-irritants nil)",
+        "(print-env 1)",
+        "(print-env (quote both))",
+    ] {
+        let err = eval_str(&mut vm, text).unwrap_err();
+        assert_eq!(err.kind_name(), "type-error", "{}", text);
+    }
+
+    Ok(())
+}
+
+/// Errors signalled within handlers are caught by outer handlers, and
+/// dynamic bindings are restored before running a handler.
+#[test]
+fn nested_handlers() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    eval_str(
+        &mut vm,
+        "(defvar *x* (quote outer))
+         (setq fail (fn (*x*) (err \"failed\")))
+         (setq rethrow (fn (e) (err (quote rethrown) \"{}\" *x*)))",
+    )?;
+
+    let value = eval_str(
+        &mut vm,
+        "(catch-error (catch-error (fail (quote inner)) rethrow) error-message)",
+    )?;
+    assert_eq!(format_object(&vm, &value), "\"outer\"");
+    assert_eq!(vm.stack.last, 0);
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::error::*;
+use crate::vm::types::VirtualMachine;
+
+/// Continuations can be used as escape procedures.
+#[test]
+fn escape() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(call/cc (fn (k) (quote unused)))")?;
+    assert_eq!(format_object(&vm, &value), "unused");
+
+    let value = eval_str(&mut vm, "(cons 1 (call/cc (fn (k) (cons 2 (k 3)))))")?;
+    assert_eq!(format_object(&vm, &value), "(1 . 3)");
+
+    let value = eval_str(&mut vm, "(call/cc (fn (k) k))")?;
+    assert!(vm.is_continuation(&value));
+    assert_eq!(vm.stack.last, 0);
+
+    Ok(())
+}
+
+/// Continuations can be re-entered after their evaluation is over.
+#[test]
+fn reentry() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(
+        &mut vm,
+        "(list 1 (call/cc (fn (k) (do (setq saved k) 2))) 3)",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(1 2 3)");
+
+    let value = eval_str(&mut vm, "(saved 10)")?;
+    assert_eq!(format_object(&vm, &value), "(1 10 3)");
+
+    let value = eval_str(&mut vm, "(cons (quote ignored) (saved 20))")?;
+    assert_eq!(format_object(&vm, &value), "(1 20 3)");
+    assert_eq!(vm.stack.last, 0);
+
+    Ok(())
+}
+
+/// Dynamic bindings are re-established when re-entering a continuation,
+/// and restored when it returns.
+#[test]
+fn reentry_dynamic_bindings() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    eval_str(
+        &mut vm,
+        "(defvar *x* (quote outer))
+         (setq f (fn (*x*) (list (call/cc (fn (k) (do (setq saved k) 1))) *x*)))",
+    )?;
+
+    let value = eval_str(&mut vm, "(f (quote inner))")?;
+    assert_eq!(format_object(&vm, &value), "(1 inner)");
+
+    let value = eval_str(&mut vm, "(list (saved 2) *x*)")?;
+    assert_eq!(format_object(&vm, &value), "(2 inner)");
+
+    let value = eval_str(&mut vm, "*x*")?;
+    assert_eq!(format_object(&vm, &value), "outer");
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::error::*;
+use crate::vm::types::VirtualMachine;
+
+/// `defvar` only assigns unbound variables, while `defparameter` always
+/// assigns them.
+#[test]
+fn declare_special() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(defvar *x* (quote first))")?;
+    assert_eq!(format_object(&vm, &value), "*x*");
+    assert!(vm.is_special(&value));
+
+    eval_str(&mut vm, "(defvar *x* (quote second))")?;
+    let value = eval_str(&mut vm, "*x*")?;
+    assert_eq!(format_object(&vm, &value), "first");
+
+    eval_str(&mut vm, "(defparameter *x* (quote third))")?;
+    let value = eval_str(&mut vm, "*x*")?;
+    assert_eq!(format_object(&vm, &value), "third");
+
+    Ok(())
+}
+
+/// Special variables bound as function parameters are visible in called
+/// functions, and restored once the binding function returns.
+#[test]
+fn dynamic_binding() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    eval_str(
+        &mut vm,
+        "(defvar *x* (quote outer))
+         (setq get-x (fn () *x*))
+         (setq with-x (fn (*x*) (get-x)))",
+    )?;
+
+    let value = eval_str(&mut vm, "(with-x (quote inner))")?;
+    assert_eq!(format_object(&vm, &value), "inner");
+
+    let value = eval_str(
+        &mut vm,
+        "(list (with-x (quote a)) (get-x) (with-x (quote b)))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(a outer b)");
+
+    let value = eval_str(&mut vm, "*x*")?;
+    assert_eq!(format_object(&vm, &value), "outer");
+
+    Ok(())
+}
+
+/// Dynamic bindings are restored when an error aborts the evaluation.
+#[test]
+fn unbind_on_error() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    eval_str(
+        &mut vm,
+        "(defvar *x* (quote outer))
+         (setq fail (fn (*x*) (car (quote oops))))",
+    )?;
+
+    assert!(eval_str(&mut vm, "(fail (quote inner))").is_err());
+
+    let value = eval_str(&mut vm, "*x*")?;
+    assert_eq!(format_object(&vm, &value), "outer");
+
+    Ok(())
+}
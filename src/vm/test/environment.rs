@@ -0,0 +1,39 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::{error::*, ConstSymbol, VirtualMachine, ENV_TABLE_SIZE};
+
+/// Allocate environments to the limits of the table, then expect an
+/// allocation error on the next one.
+#[test]
+fn environment_table_full() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    while vm.environments.last < ENV_TABLE_SIZE {
+        vm.make_environment(ConstSymbol::E0)?;
+    }
+
+    let err = vm.make_environment(ConstSymbol::E0).unwrap_err();
+    assert_eq!(err.message(), "environment area allocation error");
+    assert_eq!(err.kind_name(), "allocation-error");
+    Ok(())
+}
+
+/// Runaway recursion fills the table with an error, rather than a panic,
+/// and the machine still evaluates what needs no new environment.
+#[test]
+fn environment_table_recursion() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let err = eval_str(
+        &mut vm,
+        "(setq lp (fn (n) (cons 1 (lp n))))
+         (lp 1)",
+    )
+    .unwrap_err();
+    assert_eq!(err.kind_name(), "allocation-error");
+    assert_eq!(vm.stack.last, 0);
+
+    let value = eval_str(&mut vm, "(car (quote (1 2)))")?;
+    assert_eq!(format_object(&vm, &value), "1");
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use super::eval_str;
+use crate::vm::error::*;
+use crate::vm::types::VirtualMachine;
+
+/// Type errors report expected and actual types, and the printed value.
+#[test]
+fn type_errors() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let error = eval_str(&mut vm, "(car (quote x))").unwrap_err();
+    assert_eq!(error.type_mismatch(), Some(("cons", "symbol")));
+    assert_eq!(error.offending_value(), Some("x"));
+    assert_eq!(error.message(), "type error: expected cons, got symbol x");
+
+    let error = eval_str(&mut vm, "(apply list 1 2)").unwrap_err();
+    assert_eq!(error.type_mismatch(), Some(("list", "number")));
+    assert_eq!(error.offending_value(), Some("2"));
+
+    let error = eval_str(&mut vm, "(eval 1 (quote (a b)))").unwrap_err();
+    assert_eq!(error.type_mismatch(), Some(("environment", "cons")));
+    assert_eq!(error.offending_value(), Some("(a b)"));
+    assert_eq!(error.kind_name(), "type-error");
+
+    Ok(())
+}
+
+/// Wrong argument counts report expected and received counts.
+#[test]
+fn arg_count_errors() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let error = eval_str(&mut vm, "(cons 1)").unwrap_err();
+    assert_eq!(error.arg_count(), Some(("cons", ArgCount::Exactly(2), 1)));
+    assert_eq!(
+        error.message(),
+        "wrong number of arguments to cons: expected 2, got 1"
+    );
+
+    let error = eval_str(&mut vm, "(apply list)").unwrap_err();
+    assert_eq!(error.arg_count(), Some(("apply", ArgCount::AtLeast(2), 1)));
+
+    let error = eval_str(&mut vm, "(eval 1 2 3)").unwrap_err();
+    assert_eq!(
+        error.arg_count(),
+        Some(("eval", ArgCount::Between(1, 2), 3))
+    );
+
+    let error = eval_str(
+        &mut vm,
+        "(setq f (fn (a b) a))
+         (f 1 2 3)",
+    )
+    .unwrap_err();
+    let (_, expected, received) = error.arg_count().expect("Argument count error");
+    assert_eq!((expected, received), (ArgCount::Exactly(2), 3));
+    assert_eq!(error.kind_name(), "arity-error");
+
+    Ok(())
+}
+
+/// Applying a value which is not a function reports the value.
+#[test]
+fn not_a_function_error() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let error = eval_str(&mut vm, "(funcall 5 1)").unwrap_err();
+    assert!(error.is_not_a_function());
+    assert_eq!(error.offending_value(), Some("5"));
+    assert_eq!(error.message(), "not a function: 5");
+    assert_eq!(error.kind_name(), "not-a-function");
+    assert!(error.unbound_symbol().is_none());
+
+    Ok(())
+}
+
+/// References to unbound symbols are errors.
+#[test]
+fn unbound_variable_error() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let error = eval_str(&mut vm, "foo").unwrap_err();
+    assert_eq!(error.unbound_symbol(), Some("foo"));
+    assert_eq!(error.message(), "unbound variable: foo");
+    assert_eq!(error.kind_name(), "unbound-variable");
+
+    let error = eval_str(&mut vm, "(bar 1)").unwrap_err();
+    assert_eq!(error.unbound_symbol(), Some("bar"));
+
+    let error = eval_str(&mut vm, "(list 1 baz)").unwrap_err();
+    assert_eq!(error.unbound_symbol(), Some("baz"));
+
+    // Once assigned, symbols are no longer unbound
+    let value = eval_str(&mut vm, "(setq foo 1) foo")?;
+    assert_eq!(crate::printer::format_object(&vm, &value), "1");
+
+    Ok(())
+}
+
+/// Calling a bound symbol whose value is not a function is an error.
+#[test]
+fn call_non_function() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let error = eval_str(&mut vm, "(setq x (quote (1 2))) (x 3)").unwrap_err();
+    assert!(error.is_not_a_function());
+    assert_eq!(error.message(), "not a function: (1 2)");
+
+    let error = eval_str(&mut vm, "(1 2)").unwrap_err();
+    assert_eq!(error.message(), "not a function: 1");
+
+    Ok(())
+}
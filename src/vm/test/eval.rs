@@ -0,0 +1,123 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::error::*;
+use crate::vm::types::{DataType, VirtualMachine};
+
+/// Evaluate quoted forms on the global environment.
+#[test]
+fn eval_global() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(eval (quote (cons 1 2)))")?;
+    assert_eq!(format_object(&vm, &value), "(1 . 2)");
+
+    let value = eval_str(&mut vm, "(eval (eval (quote (quote (car (quote (a b)))))))")?;
+    assert_eq!(format_object(&vm, &value), "a");
+
+    // Global evaluation ignores the caller's lexical bindings
+    let value = eval_str(
+        &mut vm,
+        "(setq x (quote global))
+         (setq f (fn (x) (eval (quote x))))
+         (f (quote local))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "global");
+
+    Ok(())
+}
+
+/// Evaluate forms on first-class environments.
+#[test]
+fn eval_environment() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(the-environment)")?;
+    assert_eq!(value.tag, DataType::Environment);
+
+    let value = eval_str(
+        &mut vm,
+        "(setq f (fn (x) (eval (quote (cons x x)) (the-environment))))
+         (f 3)",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(3 . 3)");
+
+    // Environments outlive the call that created them
+    let value = eval_str(
+        &mut vm,
+        "(setq g (fn (y) (the-environment)))
+         (setq env (g (quote captured)))
+         (eval (quote y) env)",
+    )?;
+    assert_eq!(format_object(&vm, &value), "captured");
+
+    // Applied indirectly, the environment is still the one of the caller
+    let value = eval_str(
+        &mut vm,
+        "(setq h (fn (z) (eval (quote z) (funcall the-environment))))
+         (h (quote indirect))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "indirect");
+
+    // Even after applications among the arguments of apply and funcall
+    let value = eval_str(
+        &mut vm,
+        "(setq id (fn (v) v))
+         (setq e ((fn (x) (apply the-environment (id nil))) 5))
+         (setq e2 ((fn (x) (funcall (id the-environment))) 6))
+         (list (eval (quote x) e) (eval (quote x) e2))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(5 6)");
+
+    let err = eval_str(&mut vm, "(the-environment (g 1))").unwrap_err();
+    assert_eq!(err.kind_name(), "arity-error");
+
+    assert!(eval_str(&mut vm, "(eval 1 2)").is_err());
+    assert!(eval_str(&mut vm, "(eval)").is_err());
+
+    Ok(())
+}
+
+/// Errors inside evaluated forms leave the stack and registers usable.
+#[test]
+fn eval_errors() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    assert!(eval_str(&mut vm, "(list 1 (eval (quote (car 1 2))))").is_err());
+    assert_eq!(vm.stack.last, 0);
+
+    let value = eval_str(
+        &mut vm,
+        "(catch-error (eval (quote (err \"inner\")))
+                      (fn (e) (error-message e)))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "\"inner\"");
+
+    let value = eval_str(
+        &mut vm,
+        "(catch (quote done)
+           (list 1 (eval (quote (throw (quote done) 2)))))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "2");
+    assert_eq!(vm.stack.last, 0);
+
+    Ok(())
+}
+
+/// Built-ins receive every argument, in order.
+#[test]
+fn builtin_arguments() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    for n in 0..12 {
+        let numbers: Vec<String> = (0..n).map(|n| n.to_string()).collect();
+        let value = eval_str(&mut vm, &format!("(list {})", numbers.join(" ")))?;
+        let expected = if n == 0 {
+            "nil".to_owned()
+        } else {
+            format!("({})", numbers.join(" "))
+        };
+        assert_eq!(format_object(&vm, &value), expected);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use super::eval_str;
+use crate::printer::{format_description, format_object};
+use crate::vm::error::*;
+use crate::vm::types::VirtualMachine;
+use crate::vm::ConstSymbol;
+
+/// Closures are named after the first symbol they are bound to.
+#[test]
+fn function_names() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(fn (x) x)")?;
+    assert_eq!(format_object(&vm, &value), "#<FUNCTION (x)>");
+    assert_eq!(vm.function_name(&value), None);
+
+    let value = eval_str(&mut vm, "(setq square (fn (x) (cons x x)))")?;
+    assert_eq!(format_object(&vm, &value), "#<FUNCTION square (x)>");
+
+    // Binding to another symbol keeps the first name
+    let value = eval_str(&mut vm, "(setq other square) other")?;
+    assert_eq!(format_object(&vm, &value), "#<FUNCTION square (x)>");
+
+    let value = eval_str(&mut vm, "(defparameter *thunk* (fn () 1)) *thunk*")?;
+    assert_eq!(format_object(&vm, &value), "#<FUNCTION *thunk* ()>");
+
+    // Naming leaves the closure working
+    let value = eval_str(&mut vm, "(square 1)")?;
+    assert_eq!(format_object(&vm, &value), "(1 . 1)");
+
+    assert_eq!(format_object(&vm, &ConstSymbol::BIN_CAR), "#<BUILTIN car>");
+    assert_eq!(
+        format_object(&vm, &ConstSymbol::BIN_DESCRIBE),
+        "#<BUILTIN describe>"
+    );
+    Ok(())
+}
+
+/// Describe closures and built-in functions.
+#[test]
+fn describe_function() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(
+        &mut vm,
+        "(setq make-pair (fn (b a) (fn (x) (cons x a) (cons a b))))
+         (setq pair (make-pair 1 2))
+         pair",
+    )?;
+    assert_eq!(
+        format_description(&vm, &value)?,
+        "#<FUNCTION pair (x)>
+Lambda list: (x)
+Body:
+  (cons x a)
+  (cons a b)
+Environment:
+  a = 2
+  b = 1
+"
+    );
+
+    let value = eval_str(&mut vm, "make-pair")?;
+    assert!(format_description(&vm, &value)?.ends_with("Environment: global\n"));
+
+    assert_eq!(
+        format_description(&vm, &ConstSymbol::BIN_CONS)?,
+        "#<BUILTIN cons>\nArguments: 2\n"
+    );
+
+    let value = eval_str(&mut vm, "(describe car)")?;
+    assert_eq!(value, ConstSymbol::NIL);
+
+    let err = eval_str(&mut vm, "(describe (quote car))").unwrap_err();
+    assert_eq!(err.kind_name(), "type-error");
+    Ok(())
+}
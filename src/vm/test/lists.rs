@@ -0,0 +1,68 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::error::*;
+use crate::vm::types::VirtualMachine;
+
+/// Append copies every list but the last one.
+#[test]
+fn append() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let cases = [
+        ("(append)", "nil"),
+        ("(append (quote (1 2)))", "(1 2)"),
+        ("(append (quote (1 2)) nil (quote (3)))", "(1 2 3)"),
+        ("(append (quote (1)) 2)", "(1 . 2)"),
+    ];
+    for (text, expected) in cases {
+        let value = eval_str(&mut vm, text)?;
+        assert_eq!(format_object(&vm, &value), expected, "{}", text);
+    }
+
+    let value = eval_str(
+        &mut vm,
+        "(setq xs (quote (1 2)))
+         (setq ys (quote (3)))
+         (setq zs (append xs ys))
+         (list (eq zs xs) (eq (cdr (cdr zs)) ys))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(nil t)");
+
+    let err = eval_str(&mut vm, "(append 1 (quote (2)))").unwrap_err();
+    assert_eq!(err.kind_name(), "type-error");
+    Ok(())
+}
+
+/// Equal compares structure, numbers and strings by value.
+#[test]
+fn equal() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(
+        &mut vm,
+        "(list (equal (quote (1 (2 \"a\") . b)) (cons 1 (cons (list 2 \"a\") (quote b))))
+               (equal (quote (1 2)) (quote (1 2 3)))
+               (equal (quote a) (quote a))
+               (equal 1.5 1.5)
+               (equal \"a\" \"b\")
+               (equal car car)
+               (equal nil (quote (nil))))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(t nil t t nil t nil)");
+    Ok(())
+}
+
+/// Length counts the elements of proper lists only.
+#[test]
+fn length() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(list (length nil) (length (quote (1 (2 3) 4))))")?;
+    assert_eq!(format_object(&vm, &value), "(0 3)");
+
+    for text in ["(length (quote (1 . 2)))", "(length 1)"] {
+        let err = eval_str(&mut vm, text).unwrap_err();
+        assert_eq!(err.kind_name(), "type-error", "{}", text);
+    }
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::error::*;
+use crate::vm::types::VirtualMachine;
+use crate::vm::ConstSymbol;
+use std::path::PathBuf;
+
+/// Creates an empty temporary directory for the files of a test.
+fn temp_dir(name: &str) -> PathBuf {
+    let mut path = std::env::temp_dir();
+    path.push(format!("majestic-load-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).expect("Create test directory");
+    path
+}
+
+fn write_file(dir: &std::path::Path, name: &str, text: &str) -> String {
+    let path = dir.join(name);
+    std::fs::write(&path, text).expect("Write test file");
+    path.display().to_string()
+}
+
+/// Load files, returning the value of their last form.
+#[test]
+fn load_file() -> LispResult<()> {
+    let dir = temp_dir("file");
+    let path = write_file(
+        &dir,
+        "pair.maj",
+        "(setq a (quote first))
+         (setq b (quote second))
+         (cons a b)",
+    );
+
+    let mut vm = VirtualMachine::new();
+    let value = eval_str(&mut vm, &format!("(load \"{}\")", path))?;
+    assert_eq!(format_object(&vm, &value), "(first . second)");
+    let value = eval_str(&mut vm, "b")?;
+    assert_eq!(format_object(&vm, &value), "second");
+
+    // Loading happens on the global environment
+    let value = eval_str(
+        &mut vm,
+        &format!("(setq f (fn (a) (load \"{}\"))) (f 1) a", path),
+    )?;
+    assert_eq!(format_object(&vm, &value), "first");
+
+    // Empty files evaluate to nil
+    let path = write_file(&dir, "empty.maj", "");
+    let value = eval_str(&mut vm, &format!("(load \"{}\")", path))?;
+    assert_eq!(value, ConstSymbol::NIL);
+
+    let value = eval_str(&mut vm, "*load-files*")?;
+    assert_eq!(value, ConstSymbol::NIL);
+    Ok(())
+}
+
+/// Relative names are resolved on the directory of the loading file.
+#[test]
+fn load_relative() -> LispResult<()> {
+    let dir = temp_dir("relative");
+    std::fs::create_dir_all(dir.join("lib")).expect("Create lib directory");
+    write_file(&dir, "lib/inner.maj", "(cons (quote inner) *load-files*)");
+    let path = write_file(&dir, "outer.maj", "(load \"lib/inner\")");
+
+    let mut vm = VirtualMachine::new();
+    let value = eval_str(&mut vm, &format!("(load \"{}\")", path))?;
+    let files = vm.list_to_vec(vm.get_cdr(&value)?)?;
+    assert_eq!(files.len(), 2);
+    assert!(format_object(&vm, &files[0]).ends_with("inner.maj\""));
+    assert!(format_object(&vm, &files[1]).ends_with("outer.maj\""));
+    Ok(())
+}
+
+/// Names not found relative to the loading file are searched on the load
+/// path.
+#[test]
+fn load_search_path() -> LispResult<()> {
+    let dir = temp_dir("search");
+    write_file(&dir, "majestic-search-path-lib.maj", "(quote found)");
+
+    let mut vm = VirtualMachine::new();
+    vm.load_path = vec![dir];
+    let value = eval_str(&mut vm, "(load \"majestic-search-path-lib\")")?;
+    assert_eq!(format_object(&vm, &value), "found");
+
+    let err = eval_str(&mut vm, "(load \"majestic-no-such-file\")").unwrap_err();
+    assert_eq!(err.kind_name(), "load-error");
+    assert_eq!(vm.stack.last, 0);
+    Ok(())
+}
+
+/// Cyclic loads are reported, and leave the machine on a clean state.
+#[test]
+fn load_cycle() -> LispResult<()> {
+    let dir = temp_dir("cycle");
+    write_file(&dir, "a.maj", "(load \"b.maj\")");
+    let path = write_file(&dir, "b.maj", "(load \"a.maj\")");
+
+    let mut vm = VirtualMachine::new();
+    let err = eval_str(&mut vm, &format!("(load \"{}\")", path)).unwrap_err();
+    assert_eq!(err.kind_name(), "load-error");
+    assert!(err.to_string().contains("cyclic load"));
+    assert_eq!(vm.stack.last, 0);
+
+    let value = eval_str(&mut vm, "*load-files*")?;
+    assert_eq!(value, ConstSymbol::NIL);
+
+    let err = eval_str(&mut vm, "(load (quote a))").unwrap_err();
+    assert_eq!(err.kind_name(), "type-error");
+    Ok(())
+}
+
+/// Load files from the host, regardless of the binding of `load`.
+#[test]
+fn load_from_host() -> LispResult<()> {
+    let dir = temp_dir("host");
+    let path = write_file(&dir, "host.maj", "(setq loaded (quote yes)) loaded");
+
+    let mut vm = VirtualMachine::new();
+    eval_str(&mut vm, "(setq load (fn (x) (quote shadowed)))")?;
+    let value = vm.load_file(&path)?;
+    assert_eq!(format_object(&vm, &value), "yes");
+
+    let err = vm.load_file("majestic-no-such-file").unwrap_err();
+    assert_eq!(err.kind_name(), "load-error");
+    assert_eq!(vm.stack.last, 0);
+    Ok(())
+}
+
+/// Forms read after a load are still located on the loading file.
+#[test]
+fn load_keeps_location() -> LispResult<()> {
+    let dir = temp_dir("location");
+    let path = write_file(&dir, "lib.maj", "(setq a 1)\n(setq b 2)");
+
+    let mut vm = VirtualMachine::new();
+    let text = format!("(load \"{}\")\n(car 1 2)", path);
+    vm.add_source("main.maj", &text);
+    let error = eval_str(&mut vm, &text).unwrap_err();
+
+    let location = vm.error_location(&error).expect("Location of error");
+    let source = vm.get_source(location.file).expect("Source file");
+    assert_eq!(source.name, "main.maj");
+    assert_eq!(&text[location.span.clone()], "(car 1 2)");
+    Ok(())
+}
+
+/// Files which do not parse are reported with their parse errors.
+#[test]
+fn load_syntax_error() -> LispResult<()> {
+    let dir = temp_dir("syntax");
+    let path = write_file(&dir, "broken.maj", "(setq a 1)\n(car (");
+
+    let mut vm = VirtualMachine::new();
+    let err = eval_str(&mut vm, &format!("(load \"{}\")", path)).unwrap_err();
+    assert_eq!(err.kind_name(), "load-error");
+
+    let (file, errors) = err.syntax_errors().expect("Parse errors");
+    assert!(!errors.is_empty());
+    let source = vm.get_source(file).expect("Source file");
+    assert!(source.name.ends_with("broken.maj"));
+    assert_eq!(source.text, "(setq a 1)\n(car (");
+
+    // Nothing was evaluated
+    assert!(eval_str(&mut vm, "a").is_err());
+    Ok(())
+}
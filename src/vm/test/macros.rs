@@ -0,0 +1,123 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::error::*;
+use crate::vm::types::VirtualMachine;
+
+/// Conditionals evaluate only the chosen branch.
+#[test]
+fn conditional() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(list (if t 1 2) (if nil 1 2) (if nil 1))")?;
+    assert_eq!(format_object(&vm, &value), "(1 2 nil)");
+
+    // The other branch is never evaluated
+    let value = eval_str(&mut vm, "(if (eq 1 1) (quote yes) (car 1 2))")?;
+    assert_eq!(format_object(&vm, &value), "yes");
+
+    // Branches are in tail position
+    let value = eval_str(
+        &mut vm,
+        "(setq walk (fn (xs) (if (eq xs nil) (backtrace) (walk (cdr xs)))))
+         (walk (quote (1 2 3 4 5 6 7 8)))",
+    )?;
+    assert_eq!(vm.list_to_vec(value)?.len(), 2);
+
+    let err = eval_str(&mut vm, "(if t)").unwrap_err();
+    assert_eq!(err.arg_count(), Some(("if", ArgCount::Between(2, 3), 1)));
+    Ok(())
+}
+
+/// Dotted lambda lists bind the remaining arguments to their last symbol.
+#[test]
+fn rest_parameters() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(
+        &mut vm,
+        "(setq f (fn (a . rest) (cons a rest)))
+         (list (f 1) (f 1 2 3))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "((1) (1 2 3))");
+
+    let value = eval_str(&mut vm, "((fn args args) 1 2)")?;
+    assert_eq!(format_object(&vm, &value), "(1 2)");
+
+    let err = eval_str(&mut vm, "(f)").unwrap_err();
+    let (_, expected, received) = err.arg_count().expect("Arity error");
+    assert_eq!((expected, received), (ArgCount::AtLeast(1), 0));
+    Ok(())
+}
+
+/// Macros receive their operands unevaluated, and their expansion is
+/// evaluated in place of the application.
+#[test]
+fn macro_expansion() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(
+        &mut vm,
+        "(setq my-quote (mac (x) (list (quote quote) x)))
+         (my-quote (a b))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(a b)");
+
+    let value = eval_str(&mut vm, "my-quote")?;
+    assert_eq!(format_object(&vm, &value), "#<MACRO my-quote (x)>");
+
+    // Expansions are evaluated on the environment of the application
+    let value = eval_str(
+        &mut vm,
+        "(setq swap (mac (a b) (list (quote cons) b a)))
+         ((fn (x y) (swap x y)) 1 2)",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(2 . 1)");
+
+    // Macros can expand to other macros
+    let value = eval_str(
+        &mut vm,
+        "(setq unless (mac (p . body) (list (quote if) p nil (cons (quote do) body))))
+         (unless nil 1 2)",
+    )?;
+    assert_eq!(format_object(&vm, &value), "2");
+
+    // Macros are not functions
+    let err = eval_str(&mut vm, "(funcall swap 1 2)").unwrap_err();
+    assert_eq!(err.kind_name(), "not-a-function");
+    Ok(())
+}
+
+/// Quasiquoted templates are built with their unquoted parts evaluated.
+#[test]
+fn quasiquote() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    eval_str(&mut vm, "(setq x 1) (setq xs (quote (2 3)))")?;
+
+    let cases = [
+        ("`(a b)", "(a b)"),
+        ("`(a ,x)", "(a 1)"),
+        ("`(a ,@xs b)", "(a 2 3 b)"),
+        ("`(a . ,x)", "(a . 1)"),
+        ("`((,x) ,@xs ,@xs)", "((1) 2 3 2 3)"),
+        ("`,x", "1"),
+        // Only unquotes of the outermost level are evaluated
+        ("`(a `(b ,(c ,x)))", "(a (quasiquote (b (unquote (c 1)))))"),
+    ];
+    for (text, expected) in cases {
+        let value = eval_str(&mut vm, text)?;
+        assert_eq!(format_object(&vm, &value), expected, "{}", text);
+    }
+
+    // Templates without unquotes are not copied
+    let value = eval_str(&mut vm, "(setq f (fn () `(a b))) (eq (f) (f))")?;
+    assert_eq!(format_object(&vm, &value), "t");
+
+    // Spliced values are copied, while dotted ones are not
+    let value = eval_str(
+        &mut vm,
+        "(list (eq (cdr `(a ,@xs)) xs) (eq (cdr `(a . ,xs)) xs))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(nil t)");
+    Ok(())
+}
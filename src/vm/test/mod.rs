@@ -1,2 +1,38 @@
+mod apply;
 mod atoms;
+mod backtrace;
+mod condition;
+mod continuation;
+mod dynamic;
+mod environment;
+mod error;
+mod eval;
+mod function;
+mod lists;
+mod load;
+mod macros;
+mod nonlocal;
+mod plist;
+mod prelude;
+mod source;
 mod stack;
+
+use crate::vm::{error::LispResult, TypedPointer, VirtualMachine};
+
+/// Parses and evaluates every expression in `text`, returning the value
+/// of the last one. Panics when the text contains syntax errors.
+fn eval_str(vm: &mut VirtualMachine, text: &str) -> LispResult<TypedPointer> {
+    use chumsky::Parser;
+
+    let expressions = crate::parser::combinators::Combinators::parser()
+        .parse(text)
+        .expect("Parsed expressions");
+
+    let mut result = crate::vm::ConstSymbol::NIL;
+    for expr in expressions {
+        let ptr = crate::parser::convert::build_ast(vm, expr)?;
+        result = vm.evaluate(ptr)?;
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,176 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::constants::ConstSymbol;
+use crate::vm::error::*;
+use crate::vm::types::VirtualMachine;
+
+/// Throwing to a tag returns the thrown value from its catch form, and
+/// evaluation continues from the catch point.
+#[test]
+fn catch_throw() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(
+        &mut vm,
+        "(catch (quote a) (throw (quote a) 1) (quote unreached))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "1");
+
+    let value = eval_str(&mut vm, "(catch (quote a) (quote b))")?;
+    assert_eq!(format_object(&vm, &value), "b");
+
+    let value = eval_str(
+        &mut vm,
+        "(list 1 (catch (quote a) (cons 2 (throw (quote a) 3))) 4)",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(1 3 4)");
+
+    // Inner catch frames for other tags are skipped
+    let value = eval_str(
+        &mut vm,
+        "(catch (quote a) (cons 1 (catch (quote b) (throw (quote a) 2))))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "2");
+
+    assert_eq!(vm.stack.last, 0);
+
+    Ok(())
+}
+
+/// Throwing to a tag without a catch form is an error.
+#[test]
+fn throw_without_catch() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let error = eval_str(&mut vm, "(catch (quote a) (throw (quote b) 1))").unwrap_err();
+    assert_eq!(error.message(), "no catch for tag b");
+    assert_eq!(error.kind_name(), "control-error");
+    assert!(error.thrown().is_none());
+    assert!(!error.backtrace().is_empty());
+    assert_eq!(vm.stack.last, 0);
+
+    // Error handlers see the missing catch like any other error
+    let value = eval_str(
+        &mut vm,
+        "(catch-error (throw (quote nope) 1) (fn (e) (error-kind e)))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "control-error");
+
+    // Cleanup forms run after the error is raised, with the handler
+    // outside of them still catching it
+    let value = eval_str(
+        &mut vm,
+        "(catch-error
+           (unwind-protect (throw (quote nope) 1)
+             (put (quote log) (quote uncaught) t))
+           error-message)",
+    )?;
+    assert_eq!(format_object(&vm, &value), "\"no catch for tag nope\"");
+    let value = eval_str(&mut vm, "(get (quote log) (quote uncaught))")?;
+    assert_eq!(value, ConstSymbol::T);
+    assert_eq!(vm.stack.last, 0);
+
+    Ok(())
+}
+
+/// Cleanup forms are evaluated when the protected form returns normally,
+/// without changing its value.
+#[test]
+fn unwind_protect_normal_exit() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(
+        &mut vm,
+        "(unwind-protect (quote value) (put (quote log) (quote normal) t))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "value");
+
+    let value = eval_str(&mut vm, "(get (quote log) (quote normal))")?;
+    assert_eq!(value, ConstSymbol::T);
+
+    Ok(())
+}
+
+/// Cleanup forms are evaluated when a throw or an error unwinds through
+/// them, even when the error is not handled.
+#[test]
+fn unwind_protect_nonlocal_exit() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(
+        &mut vm,
+        "(catch (quote a)
+           (unwind-protect (throw (quote a) 1)
+             (put (quote log) (quote throw) t)))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "1");
+
+    let value = eval_str(
+        &mut vm,
+        "(catch-error
+           (unwind-protect (err \"failed\")
+             (put (quote log) (quote error) t))
+           error-message)",
+    )?;
+    assert_eq!(format_object(&vm, &value), "\"failed\"");
+
+    let error = eval_str(
+        &mut vm,
+        "(unwind-protect (car (quote x))
+           (put (quote log) (quote unhandled) t))",
+    );
+    assert!(error.is_err());
+
+    let value = eval_str(
+        &mut vm,
+        "(list (get (quote log) (quote throw))
+               (get (quote log) (quote error))
+               (get (quote log) (quote unhandled)))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(t t t)");
+    assert_eq!(vm.stack.last, 0);
+
+    Ok(())
+}
+
+/// Errors unwinding through cleanup forms are raised again unchanged.
+#[test]
+fn unwind_protect_keeps_error() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let err = eval_str(&mut vm, "(unwind-protect (car 1 2) nil)").unwrap_err();
+    assert_eq!(err.arg_count(), Some(("car", ArgCount::Exactly(1), 2)));
+    assert!(!err.backtrace().is_empty());
+
+    // Errors handled by the cleanup forms leave the pending one alone
+    let err = eval_str(
+        &mut vm,
+        "(unwind-protect (car (quote x))
+           (catch-error (cdr 1 2) error-message))",
+    )
+    .unwrap_err();
+    assert_eq!(err.type_mismatch(), Some(("cons", "symbol")));
+    assert!(vm.pending_errors.is_empty());
+
+    Ok(())
+}
+
+/// Dynamic bindings are restored when throwing out of a function.
+#[test]
+fn throw_restores_dynamic_bindings() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    eval_str(
+        &mut vm,
+        "(defvar *x* (quote outer))
+         (setq escape (fn (*x*) (throw (quote done) *x*)))",
+    )?;
+
+    let value = eval_str(
+        &mut vm,
+        "(list (catch (quote done) (escape (quote inner))) *x*)",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(inner outer)");
+
+    Ok(())
+}
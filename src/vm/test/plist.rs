@@ -0,0 +1,107 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::constants::ConstSymbol;
+use crate::vm::error::*;
+use crate::vm::types::{Number, VirtualMachine};
+
+/// New atoms start with an empty property list.
+#[test]
+fn empty_plist() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+    let atom = vm.make_atom("foo")?;
+
+    assert_eq!(vm.symbol_plist(atom.clone())?, ConstSymbol::NIL);
+    assert_eq!(vm.get_property(atom, ConstSymbol::T)?, ConstSymbol::NIL);
+
+    Ok(())
+}
+
+/// Put, replace and remove properties through the VM interface.
+#[test]
+fn put_get_remove() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+    let atom = vm.make_atom("foo")?;
+    let doc = vm.make_atom("doc")?;
+    let kind = vm.make_atom("type")?;
+    let number = vm.make_number(Number::Integer(5))?;
+
+    vm.put_property(atom.clone(), doc.clone(), ConstSymbol::T)?;
+    vm.put_property(atom.clone(), kind.clone(), number.clone())?;
+    assert_eq!(vm.get_property(atom.clone(), doc.clone())?, ConstSymbol::T);
+    assert_eq!(vm.get_property(atom.clone(), kind.clone())?, number);
+    assert_eq!(
+        format_object(&vm, &vm.symbol_plist(atom.clone())?),
+        "(type 5 doc t)"
+    );
+
+    // Replacing a property must not grow the plist
+    vm.put_property(atom.clone(), doc.clone(), ConstSymbol::NIL)?;
+    assert_eq!(
+        format_object(&vm, &vm.symbol_plist(atom.clone())?),
+        "(type 5 doc nil)"
+    );
+
+    assert!(vm.remove_property(atom.clone(), kind.clone())?);
+    assert!(!vm.remove_property(atom.clone(), kind)?);
+    assert!(vm.remove_property(atom.clone(), doc)?);
+    assert_eq!(vm.symbol_plist(atom)?, ConstSymbol::NIL);
+
+    Ok(())
+}
+
+/// Property lists cannot be accessed on non-atoms.
+#[test]
+fn plist_of_non_atom() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+    let number = vm.make_number(Number::Integer(1))?;
+
+    assert!(vm.symbol_plist(number.clone()).is_err());
+    assert!(vm.get_property(number.clone(), ConstSymbol::T).is_err());
+    assert!(vm
+        .put_property(number, ConstSymbol::T, ConstSymbol::T)
+        .is_err());
+
+    Ok(())
+}
+
+/// Use the built-in functions from Lisp code.
+#[test]
+fn plist_builtins() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(&mut vm, "(put (quote foo) (quote doc) (quote bar))")?;
+    assert_eq!(format_object(&vm, &value), "bar");
+
+    let value = eval_str(&mut vm, "(get (quote foo) (quote doc))")?;
+    assert_eq!(format_object(&vm, &value), "bar");
+
+    let value = eval_str(&mut vm, "(symbol-plist (quote foo))")?;
+    assert_eq!(format_object(&vm, &value), "(doc bar)");
+
+    let value = eval_str(&mut vm, "(remprop (quote foo) (quote doc))")?;
+    assert_eq!(value, ConstSymbol::T);
+
+    let value = eval_str(&mut vm, "(get (quote foo) (quote doc))")?;
+    assert_eq!(value, ConstSymbol::NIL);
+
+    Ok(())
+}
+
+/// Property keys are compared as `eq` does, so numbers match by value.
+#[test]
+fn number_keys() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let value = eval_str(
+        &mut vm,
+        "(put (quote x) 1 (quote a))
+         (put (quote x) 1 (quote b))
+         (list (get (quote x) 1) (symbol-plist (quote x)))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(b (1 b))");
+
+    let value = eval_str(&mut vm, "(remprop (quote x) 1) (symbol-plist (quote x))")?;
+    assert_eq!(format_object(&vm, &value), "nil");
+
+    Ok(())
+}
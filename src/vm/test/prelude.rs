@@ -0,0 +1,131 @@
+use super::eval_str;
+use crate::printer::format_object;
+use crate::vm::error::*;
+use crate::vm::prelude::PRELUDE;
+use crate::vm::types::VirtualMachine;
+
+/// Every form of the prelude evaluates without error.
+#[test]
+fn prelude_forms() -> LispResult<()> {
+    use chumsky::Parser;
+
+    let mut vm = VirtualMachine::new();
+    let expressions = crate::parser::combinators::Combinators::parser()
+        .parse(PRELUDE)
+        .expect("Parsed prelude");
+    assert!(!expressions.is_empty());
+
+    for expr in expressions {
+        let ptr = crate::parser::convert::build_ast(&mut vm, expr)?;
+        let form = format_object(&vm, &ptr);
+        if let Err(e) = vm.evaluate(ptr) {
+            panic!("Prelude form {} failed: {}", form, e);
+        }
+    }
+
+    assert_eq!(vm.stack.last, 0);
+    Ok(())
+}
+
+/// Prelude definitions are available on machines created with it.
+#[test]
+fn prelude_definitions() -> LispResult<()> {
+    let mut vm = VirtualMachine::with_prelude()?;
+
+    let value = eval_str(&mut vm, "(cadr (quote (1 2 3)))")?;
+    assert_eq!(format_object(&vm, &value), "2");
+
+    let value = eval_str(
+        &mut vm,
+        "(list (nilp nil) (not (quote a)) (third (quote (1 2 3))))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(t nil 3)");
+
+    let value = eval_str(&mut vm, "((compose car cddr) (quote (1 2 3)))")?;
+    assert_eq!(format_object(&vm, &value), "3");
+
+    Ok(())
+}
+
+/// The prelude defines the usual definition, conditional and binding
+/// macros, which expand to the special forms of the evaluator.
+#[test]
+fn prelude_macros() -> LispResult<()> {
+    let mut vm = VirtualMachine::with_prelude()?;
+
+    let value = eval_str(
+        &mut vm,
+        "(defn swap (pair) (cons (cdr pair) (car pair)))
+         (swap (quote (1 . 2)))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(2 . 1)");
+
+    let value = eval_str(
+        &mut vm,
+        "(list (when t 1 2) (when nil 1) (unless nil 3) (unless t 4))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(2 nil 3 nil)");
+
+    let value = eval_str(
+        &mut vm,
+        "(defn classify (x)
+           (cond ((nilp x) (quote empty))
+                 ((equal x (quote (1))) (quote one))
+                 (t (quote many))))
+         (list (classify nil) (classify (list 1)) (classify (quote (1 2))))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(empty one many)");
+
+    let value = eval_str(&mut vm, "(let ((x 1) (y (quote (2)))) (cons x y))")?;
+    assert_eq!(format_object(&vm, &value), "(1 2)");
+
+    let value = eval_str(
+        &mut vm,
+        "(setq alist (list (cons \"a\" 1) (cons (quote (b)) 2)))
+         (list (assoc (quote (b)) alist) (assoc \"a\" alist) (assoc (quote c) alist))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(((b) . 2) (\"a\" . 1) nil)");
+
+    let value = eval_str(&mut vm, "(map car (quote ((1 2) (3 4))))")?;
+    assert_eq!(format_object(&vm, &value), "(1 3)");
+
+    let value = eval_str(
+        &mut vm,
+        "(setq xs (quote (1 (2) 3)))
+         (list (member (list 2) xs) (member 4 xs) (length xs))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(((2) 3) nil 3)");
+
+    let value = eval_str(
+        &mut vm,
+        "(list (mapc (fn (x) (put (quote seen) x t)) (quote (a b)))
+               (get (quote seen) (quote a))
+               (get (quote seen) (quote b)))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "(nil t t)");
+
+    let value = eval_str(
+        &mut vm,
+        "(list (let* ((x 1) (y (cons x x))) y)
+               (let* () 2)
+               (letfn ((twice (x) (cons x x)) (one () 1)) (twice (one))))",
+    )?;
+    assert_eq!(format_object(&vm, &value), "((1 . 1) 2 (1 . 1))");
+
+    let value = eval_str(&mut vm, "(list defn map)")?;
+    assert_eq!(
+        format_object(&vm, &value),
+        "(#<MACRO defn (label lambda-list . body)> #<FUNCTION map (f xs)>)"
+    );
+
+    Ok(())
+}
+
+/// Machines without prelude know nothing about it.
+#[test]
+fn no_prelude() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+    let err = eval_str(&mut vm, "(cadr (quote (1 2)))").unwrap_err();
+    assert_eq!(err.unbound_symbol(), Some("cadr"));
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use super::eval_str;
+use crate::vm::error::*;
+use crate::vm::types::VirtualMachine;
+
+/// Conses read from source are mapped to their location.
+#[test]
+fn source_locations() -> LispResult<()> {
+    use chumsky::Parser;
+
+    let mut vm = VirtualMachine::new();
+    let text = "(cons 1 (quote (a b)))";
+    let file = vm.add_source("test.maj", text);
+
+    let expr = crate::parser::combinators::Combinators::parser()
+        .parse(text)
+        .expect("Parsed expressions")
+        .remove(0);
+    let ptr = crate::parser::convert::build_ast(&mut vm, expr)?;
+
+    let location = vm.get_location(&ptr).expect("Location of form");
+    assert_eq!(location.file, file);
+    assert_eq!(&text[location.span.clone()], text);
+
+    // (quote (a b)), then (a b)
+    let quoted = vm.get_car(&vm.get_cdr(&vm.get_cdr(&ptr)?)?)?;
+    let location = vm.get_location(&quoted).expect("Location of quoted form");
+    assert_eq!(&text[location.span.clone()], "(quote (a b))");
+
+    let list = vm.get_car(&vm.get_cdr(&quoted)?)?;
+    let location = vm.get_location(&list).expect("Location of list");
+    assert_eq!(&text[location.span.clone()], "(a b)");
+
+    // Conses built at runtime have no location
+    let value = vm.evaluate(ptr)?;
+    assert!(vm.get_location(&value).is_none());
+
+    assert_eq!(
+        vm.get_source(file).map(|f| f.name.as_str()),
+        Some("test.maj")
+    );
+
+    Ok(())
+}
+
+/// Errors are located on the innermost form read from source.
+#[test]
+fn error_location() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let text = "(setq f (fn (x) (list x (car x 2))))";
+    vm.add_source("f.maj", text);
+    eval_str(&mut vm, text)?;
+
+    vm.add_source("<repl>", "(f 1)");
+    let error = eval_str(&mut vm, "(f 1)").unwrap_err();
+
+    let location = vm.error_location(&error).expect("Location of error");
+    let source = vm.get_source(location.file).expect("Source file");
+    assert_eq!(source.name, "f.maj");
+    assert_eq!(&text[location.span.clone()], "(car x 2)");
+
+    Ok(())
+}
+
+/// Sources registered again under the same name replace the previous text.
+#[test]
+fn replace_source() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+
+    let file = vm.replace_source("<repl>", "(car 1 2)");
+    let error = eval_str(&mut vm, "(car 1 2)").unwrap_err();
+    assert!(vm.error_location(&error).is_some());
+
+    assert_eq!(vm.replace_source("<repl>", "(cdr 1 2)"), file);
+    assert_eq!(vm.sources.files.len(), 1);
+    assert_eq!(
+        vm.get_source(file).map(|f| f.text.as_str()),
+        Some("(cdr 1 2)")
+    );
+
+    // Locations on the previous text are gone
+    assert!(vm.error_location(&error).is_none());
+    Ok(())
+}
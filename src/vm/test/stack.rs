@@ -1,3 +1,4 @@
+use super::eval_str;
 use crate::vm::{error::*, ConstSymbol, Number, VirtualMachine};
 
 /// Simple test for pushing, popping and peeking from VM stack.
@@ -77,3 +78,31 @@ fn unwind() -> LispResult<()> {
 
     Ok(())
 }
+
+/// Recover from a failed evaluation: the stack returns to its previous
+/// depth, registers are restored and discarded entries are reported.
+#[test]
+fn recover_after_error() -> LispResult<()> {
+    let mut vm = VirtualMachine::new();
+    eval_str(&mut vm, "(setq x 1)")?;
+    let registers = vm.registers.clone();
+
+    let error = eval_str(&mut vm, "(list 1 (list 2 (car 3 4)))").unwrap_err();
+    assert!(error.discarded() > 0);
+    assert_eq!(vm.stack.last, 0);
+    assert_eq!(vm.registers, registers);
+
+    // Evaluation keeps working on a clean state
+    let value = eval_str(&mut vm, "(cons x x)")?;
+    assert_eq!(crate::printer::format_object(&vm, &value), "(1 . 1)");
+
+    // Explicit recovery discards whatever was left on the stack
+    for _ in 0..3 {
+        vm.stack_push(ConstSymbol::T)?;
+    }
+    assert_eq!(vm.recover()?, 3);
+    assert_eq!(vm.recover()?, 0);
+    assert!(vm.stack_peek().is_err());
+
+    Ok(())
+}
@@ -3,6 +3,7 @@
 // pub const LIST_AREA_SIZE: usize = 16777216; // 16MB list area
 // pub const LISP_STACK_SIZE: usize = 8388608; // 8MB stack
 
+use super::error::LispError;
 use super::ConstSymbol;
 use radix_trie::Trie;
 
@@ -10,6 +11,7 @@ pub const ATOM_TABLE_SIZE: usize = 10000; // 30000 atoms
 pub const NUMBER_TABLE_SIZE: usize = 10000; // 30000 numbers (indexed after atom table)
 pub const LIST_AREA_SIZE: usize = 524288; // # of cells, total 16MB
 pub const LISP_STACK_SIZE: usize = 524288; // # of pointers, total 8MB
+pub const STRING_TABLE_SIZE: usize = 10000; // 10000 strings
 pub const ENV_TABLE_SIZE: usize = 2000; // 1000 environments
 pub const MAX_ENV_CAPACITY: usize = 200; // Each env can contain at most 200 bindings
 
@@ -24,8 +26,29 @@ pub enum DataType {
     BuiltInFunction,
     BuiltInLiteral,
     Function,
+    Macro,
     Literal,
     Environment,
+    String,
+}
+
+impl DataType {
+    /// Name of the type as seen from Lisp code.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            DataType::Undefined => "undefined",
+            DataType::Cons => "cons",
+            DataType::Atom => "symbol",
+            DataType::Number => "number",
+            DataType::BuiltInFunction => "builtin-function",
+            DataType::BuiltInLiteral => "builtin-literal",
+            DataType::Function => "function",
+            DataType::Macro => "macro",
+            DataType::Literal => "literal",
+            DataType::Environment => "environment",
+            DataType::String => "string",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -59,8 +82,10 @@ impl std::fmt::Display for TypedPointer {
                 DataType::BuiltInFunction => "BINFN",
                 DataType::BuiltInLiteral => "BINLT",
                 DataType::Function => "FUNCT",
+                DataType::Macro => "MACRO",
                 DataType::Literal => "LITER",
                 DataType::Environment => "  ENV",
+                DataType::String => "  STR",
                 #[allow(unreachable_patterns)]
                 _ => "UNKNW",
             },
@@ -80,8 +105,14 @@ pub struct Cons {
 pub struct Atom {
     pub name: String,
     pub value: TypedPointer,
-    // pub plist: UntypedPointer,
-    // pub bindlist: UntypedPointer,
+    /// Property list, stored as a flat list `(key1 value1 key2 value2 ...)`
+    /// on the list area, so it is reachable from the atom table itself.
+    pub plist: TypedPointer,
+    /// Values shadowed by dynamic bindings of a special variable, most recent
+    /// first. The current dynamic value is always kept on `value`.
+    pub bindlist: TypedPointer,
+    /// Whether the atom was declared as a special (dynamically scoped) variable.
+    pub special: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -139,9 +170,24 @@ pub struct RegisterArea {
     pub unev: TypedPointer,
 }
 
+/// Next step of the evaluator.
+/// - `Dispatch`: Evaluate the expression on `exp`.
+/// - `Continue`: Go to the place on `continue`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum EvalStep {
+    #[default]
+    Dispatch,
+    Continue,
+}
+
+/// Stack area for the virtual machine.
+/// - `last`: Position of the next push.
+/// - `base`: Position where the segment of the current evaluation begins.
+/// - `area`: Stack contents.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct StackArea {
     pub last: UntypedPointer,
+    pub base: UntypedPointer,
     pub area: Vec<TypedPointer>,
 }
 
@@ -149,6 +195,7 @@ impl Default for StackArea {
     fn default() -> Self {
         Self {
             last: 0,
+            base: 0,
             area: (0..LISP_STACK_SIZE)
                 .map(|_| TypedPointer::default())
                 .collect(),
@@ -200,6 +247,21 @@ impl NumberTable {
     }
 }
 
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StringTable {
+    pub last: UntypedPointer,
+    pub area: Vec<String>,
+}
+
+impl Default for StringTable {
+    fn default() -> Self {
+        Self {
+            last: 0,
+            area: (0..STRING_TABLE_SIZE).map(|_| String::new()).collect(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ListArea {
     pub last: UntypedPointer,
@@ -247,14 +309,64 @@ impl Default for EnvironmentTable {
     }
 }
 
+/// Source file known to the virtual machine.
+/// - `name`: File name, or a placeholder such as `<repl>`.
+/// - `text`: Full source text.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct SourceFile {
+    pub name: String,
+    pub text: String,
+}
+
+/// Location of an expression on a source file.
+/// - `file`: Index of the file on the source table.
+/// - `span`: Character range of the expression.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct SourceLocation {
+    pub file: usize,
+    pub span: std::ops::Range<usize>,
+}
+
+/// Source table, mapping cons cells read from source to their locations.
+/// - `files`: Source files read so far.
+/// - `current`: File whose expressions are being read, if any.
+/// - `locations`: Locations of cons cells, indexed by untyped pointer.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct SourceTable {
+    pub files: Vec<SourceFile>,
+    pub current: Option<usize>,
+    pub locations: std::collections::HashMap<UntypedPointer, SourceLocation>,
+}
+
+/// Application frame, recorded for backtraces.
+/// - `form`: Application form being evaluated.
+/// - `fun`: Function being applied, once the operator is evaluated.
+/// - `depth`: Stack depth at the start of the application.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Frame {
+    pub form: TypedPointer,
+    pub fun: TypedPointer,
+    pub depth: UntypedPointer,
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct VirtualMachine {
     pub registers: RegisterArea,
+    pub step: EvalStep,
     pub stack: StackArea,
     pub atoms: AtomTable,
     pub numbers: NumberTable,
+    pub strings: StringTable,
     pub lists: ListArea,
     pub environments: EnvironmentTable,
+    pub frames: Vec<Frame>,
+    pub sources: SourceTable,
+    /// Errors whose exit is suspended while the cleanup forms of an
+    /// unwind-protect run, by the error object saved on the stack.
+    pub pending_errors: Vec<(TypedPointer, LispError)>,
+    /// Directories searched by `load` for relative file names, after the
+    /// directory of the loading file.
+    pub load_path: Vec<std::path::PathBuf>,
 
     pub atom_index: Trie<String, usize>,
 }
@@ -8,3 +8,4 @@ mod util;
 
 mod ast;
 mod printer;
+mod script;
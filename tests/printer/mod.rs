@@ -93,6 +93,21 @@ fn format_atom() {
     generate_ast_test!(vm, "complex");
     generate_ast_test!(vm, "vector");
     generate_ast_test!(vm, "setq");
+    generate_ast_test!(vm, "defvar");
+    generate_ast_test!(vm, "defparameter");
+    generate_ast_test!(vm, "catch-error");
+    generate_ast_test!(vm, "catch");
+    generate_ast_test!(vm, "unwind-protect");
+    generate_ast_test!(vm, "if");
+
+    // Reserved symbols
+    generate_ast_test!(vm, "throw");
+    generate_ast_test!(vm, "continuation");
+    generate_ast_test!(vm, "*load-files*");
+    generate_ast_test!(vm, "*print-length*");
+    generate_ast_test!(vm, "*print-level*");
+    generate_ast_test!(vm, "*print-right-margin*");
+    generate_ast_test!(vm, "*print-circle*");
 
     assert_eq!(
         vm.atoms.last, num_registered_atoms,
@@ -120,6 +135,15 @@ fn format_atom() {
     );
 }
 
+// string
+#[test]
+fn format_string() {
+    let mut vm = vm::VirtualMachine::new();
+    generate_ast_test!(vm, "\"hello\"");
+    generate_ast_test!(vm, "\"hello, world\"");
+    generate_ast_test!(vm, "\"(not a list)\"");
+}
+
 // function
 #[test]
 fn format_function() {
@@ -156,7 +180,29 @@ fn format_builtin_function() {
         ConstSymbol::BIN_CAR,
         ConstSymbol::BIN_CDR,
         ConstSymbol::BIN_EVAL,
-        ConstSymbol::BIN_EQ
+        ConstSymbol::BIN_EQ,
+        ConstSymbol::BIN_GET,
+        ConstSymbol::BIN_PUT,
+        ConstSymbol::BIN_REMPROP,
+        ConstSymbol::BIN_SYMBOL_PLIST,
+        ConstSymbol::BIN_PRINT_ENV,
+        ConstSymbol::BIN_ERR,
+        ConstSymbol::BIN_ERRORP,
+        ConstSymbol::BIN_ERROR_KIND,
+        ConstSymbol::BIN_ERROR_MESSAGE,
+        ConstSymbol::BIN_ERROR_IRRITANTS,
+        ConstSymbol::BIN_THROW,
+        ConstSymbol::BIN_CALL_CC,
+        ConstSymbol::BIN_APPLY,
+        ConstSymbol::BIN_FUNCALL,
+        ConstSymbol::BIN_THE_ENVIRONMENT,
+        ConstSymbol::BIN_BACKTRACE,
+        ConstSymbol::BIN_LOAD,
+        ConstSymbol::BIN_PPRINT,
+        ConstSymbol::BIN_DESCRIBE,
+        ConstSymbol::BIN_APPEND,
+        ConstSymbol::BIN_EQUAL,
+        ConstSymbol::BIN_LENGTH
     });
 }
 
@@ -173,7 +219,23 @@ fn format_builtin_literal() {
         ConstSymbol::EVAL_ARGS,
         ConstSymbol::ACCUMULATE_ARG,
         ConstSymbol::ACCUMULATE_LAST_ARG,
-        ConstSymbol::EVAL_ASSIGN
+        ConstSymbol::EVAL_ASSIGN,
+        ConstSymbol::EVAL_DEFINE,
+        ConstSymbol::UNBIND_DYNAMIC,
+        ConstSymbol::EVAL_SEQUENCE_CONTINUE,
+        ConstSymbol::DYNAMIC_FRAME,
+        ConstSymbol::POP_ERROR_HANDLER,
+        ConstSymbol::APPLY_ERROR_HANDLER,
+        ConstSymbol::ERROR_HANDLER_FRAME,
+        ConstSymbol::CATCH_TAG_EVALUATED,
+        ConstSymbol::POP_CATCH,
+        ConstSymbol::UNWIND_PROTECT_CLEANUP,
+        ConstSymbol::UNWIND_PROTECT_RETURN,
+        ConstSymbol::RESUME_UNWIND,
+        ConstSymbol::CATCH_FRAME,
+        ConstSymbol::UNWIND_PROTECT_FRAME,
+        ConstSymbol::IF_DECIDE,
+        ConstSymbol::EVAL_EXPANSION
     });
 }
 
@@ -0,0 +1,224 @@
+//! Test module for running scripts with the majestic binary.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Writes a script to a temporary file, then runs it with the given
+/// arguments, returning the exit status and standard output of the binary.
+fn run_script(name: &str, text: &str, args: &[&str]) -> (Option<i32>, String) {
+    run_script_with(&[], name, text, args)
+}
+
+/// Like `run_script`, passing options to the binary before the file name.
+fn run_script_with(
+    options: &[&str],
+    name: &str,
+    text: &str,
+    args: &[&str],
+) -> (Option<i32>, String) {
+    let mut path: PathBuf = std::env::temp_dir();
+    path.push(format!("majestic-test-{}-{}.maj", std::process::id(), name));
+    std::fs::write(&path, text).expect("Write script file");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_majestic"))
+        .args(options)
+        .arg(&path)
+        .args(args)
+        .output()
+        .expect("Run majestic binary");
+
+    std::fs::remove_file(&path).expect("Remove script file");
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+    )
+}
+
+#[test]
+fn script_success() {
+    // Values of top-level forms are not printed
+    let text = "(setq first (car *argv*))
+                (setq second (car (cdr *argv*)))
+                (cons first second)";
+    assert_eq!(
+        run_script("success", text, &["a", "b"]),
+        (Some(0), "".into())
+    );
+    assert_eq!(run_script("empty", "", &[]), (Some(0), "".into()));
+
+    let text = "(pprint (cons (quote a) (quote b)))";
+    assert_eq!(
+        run_script("print", text, &[]),
+        (Some(0), "(a . b)\n".into())
+    );
+}
+
+#[test]
+fn script_argv() {
+    let text = "(pprint *argv*)";
+    assert_eq!(
+        run_script("argv", text, &["a", "b c"]),
+        (Some(0), "(\"a\" \"b c\")\n".into())
+    );
+    assert_eq!(
+        run_script("argv-empty", text, &[]),
+        (Some(0), "nil\n".into())
+    );
+
+    // Taking the car of the empty *argv* yields nil, while taking the car
+    // of a string is an error
+    let text = "(pprint (car (car *argv*)))";
+    assert_eq!(run_script("argv-nil", text, &[]), (Some(0), "nil\n".into()));
+    assert_eq!(
+        run_script("argv-string", text, &["x"]),
+        (Some(1), "".into())
+    );
+}
+
+#[test]
+fn script_errors() {
+    // Forms before the error are evaluated, and errors go to stderr
+    let text = "(pprint 1)\n(car 1 2)\n(pprint 2)";
+    assert_eq!(run_script("runtime", text, &[]), (Some(1), "1\n".into()));
+
+    // Nothing is evaluated when the script does not parse
+    let text = "(pprint 1)\n(car (";
+    assert_eq!(run_script("parse", text, &[]), (Some(2), "".into()));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_majestic"))
+        .arg("this-file-does-not-exist.maj")
+        .current_dir(std::env::temp_dir())
+        .output()
+        .expect("Run majestic binary");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn script_prelude() {
+    let text = "(pprint (cadr (quote (1 2))))";
+    assert_eq!(run_script("prelude", text, &[]), (Some(0), "2\n".into()));
+    assert_eq!(
+        run_script_with(&["--no-prelude"], "no-prelude", text, &[]),
+        (Some(1), "".into())
+    );
+}
+
+#[test]
+fn script_eval() {
+    let output = Command::new(env!("CARGO_BIN_EXE_majestic"))
+        .args(["-e", "(car (quote (1 2)))", "--eval", "(cons 1 2)"])
+        .current_dir(std::env::temp_dir())
+        .output()
+        .expect("Run majestic binary");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n(1 . 2)\n");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_majestic"))
+        .args(["-e", "(car 1)", "-e", "(cons 1 2)"])
+        .current_dir(std::env::temp_dir())
+        .output()
+        .expect("Run majestic binary");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+
+    // Errors without a location are reported by their message alone
+    let output = Command::new(env!("CARGO_BIN_EXE_majestic"))
+        .args(["-e", "(setq 1 2)"])
+        .current_dir(std::env::temp_dir())
+        .output()
+        .expect("Run majestic binary");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Error during evaluation: type error: expected symbol, got number 1\n"
+    );
+
+    // Expressions see the remaining arguments on *argv*
+    let output = Command::new(env!("CARGO_BIN_EXE_majestic"))
+        .args(["-e", "*argv*", "a", "b"])
+        .current_dir(std::env::temp_dir())
+        .output()
+        .expect("Run majestic binary");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "(\"a\" \"b\")\n");
+}
+
+#[test]
+fn script_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    // Both an explicit `-` and a non-terminal standard input read the
+    // program from stdin, without the REPL banner
+    for args in [&["-"][..], &[]] {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_majestic"))
+            .args(args)
+            .current_dir(std::env::temp_dir())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Run majestic binary");
+        child
+            .stdin
+            .take()
+            .expect("Open stdin")
+            .write_all(b"(car (quote (1 2)))\n(cdr (quote (1 2)))\n")
+            .expect("Write to stdin");
+
+        let output = child.wait_with_output().expect("Wait for majestic binary");
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n(2)\n");
+    }
+}
+
+#[test]
+fn script_log_config() {
+    // The repository root holds a logcfg.yml which logs to stdout, yet
+    // batch runs only print their results
+    let output = Command::new(env!("CARGO_BIN_EXE_majestic"))
+        .args(["-e", "(cons 1 2)"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("Run majestic binary");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "(1 . 2)\n");
+}
+
+#[test]
+fn script_trailing_comment() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    // Comments may end the input without a newline
+    let output = Command::new(env!("CARGO_BIN_EXE_majestic"))
+        .args(["-e", "(car (quote (1 2))) ; c"])
+        .current_dir(std::env::temp_dir())
+        .output()
+        .expect("Run majestic binary");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_majestic"))
+        .arg("-")
+        .current_dir(std::env::temp_dir())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Run majestic binary");
+    child
+        .stdin
+        .take()
+        .expect("Open stdin")
+        .write_all(b"(car (quote (1 2)))\n(cdr (quote (1 2))) ; last")
+        .expect("Write to stdin");
+
+    let output = child.wait_with_output().expect("Wait for majestic binary");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n(2)\n");
+
+    assert_eq!(
+        run_script("comment", "(pprint 1) ; done", &[]),
+        (Some(0), "1\n".into())
+    );
+}
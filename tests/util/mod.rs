@@ -91,8 +91,8 @@ impl RegularExpression {
 
     /// Regular expression for function textual format.
     /// ### Example
-    /// `#<FUNCTION {0xdeadbeef}>`
-    pub const FUNCTION: &str = r"(?u)^#<FUNCTION \{0x[0-9a-z]*\}>$";
+    /// `#<FUNCTION square (x)>`
+    pub const FUNCTION: &str = r"(?u)^#<FUNCTION ([^ ()]+ )?\(.*\)>$";
 
     /// Regular expression for literal textual format.
     /// ### Example
@@ -101,8 +101,8 @@ impl RegularExpression {
 
     /// Regular expression for built-in function textual format.
     /// ### Example
-    /// `#<BUILTIN-FUNCTION {0xdeadbeef}>`
-    pub const BUILTIN_FUNCTION: &str = r"(?u)^#<BUILTIN-FUNCTION \{0x[0-9a-z]*\}>$";
+    /// `#<BUILTIN car>`
+    pub const BUILTIN_FUNCTION: &str = r"(?u)^#<BUILTIN [^ {}]+>$";
 
     /// Regular expression for built-in literal textual format.
     /// ### Example